@@ -17,6 +17,37 @@ pub enum ChainOrdering {
     // The ordering of this chain is unknown.
     Unknown,
 }
+
+/// The predicted outcome of pushing a block, determined without pushing it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BlockClassification {
+    // The block is already known.
+    Known,
+    // The block extends the main chain.
+    Extend,
+    // The block is on a chain that is better than the main chain. Pushing it would rebranch.
+    Better,
+    // The block is on a chain that is worse than the main chain or precedes the last macro block.
+    Inferior,
+    // The block is on a fork whose ordering is unknown.
+    Fork,
+    // The parent of the block is unknown.
+    Orphan,
+}
+
+/// Detailed result of comparing one chain to the main chain, mostly useful for diagnostics.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ChainAnalysis {
+    /// The ordering of the new chain relative to the main chain.
+    pub ordering: ChainOrdering,
+    /// The block number of the common ancestor of both chains, if the chains were compared.
+    pub fork_height: Option<u32>,
+    /// The block number of the first skip block after the fork on the main chain, if any.
+    pub our_skip_block: Option<u32>,
+    /// The block number of the first skip block after the fork on the new chain, if any.
+    pub their_skip_block: Option<u32>,
+}
+
 /// Implements method to calculate chain ordering.
 impl ChainOrdering {
     /// Given a block and some chain, it returns the ordering of the new chain relative to the given
@@ -31,11 +62,45 @@ impl ChainOrdering {
         get_chain_info: F,
         get_block_at: G,
     ) -> ChainOrdering
+    where
+        F: Fn(&Blake2bHash) -> Result<ChainInfo, BlockchainError>,
+        G: Fn(u32) -> Result<Block, BlockchainError>,
+    {
+        Self::analyze_chains(blockchain, block, prev_info, get_chain_info, get_block_at).ordering
+    }
+
+    /// Breaks the tie between the chain ending in `block` and the main chain ending in `head` if
+    /// their ordering is unknown and both have the same height: the chain whose head has the
+    /// lexicographically smaller hash is superior. Any other ordering is returned unchanged.
+    pub fn tie_break(self, block: &Block, head: &Block) -> ChainOrdering {
+        if self != ChainOrdering::Unknown || block.block_number() != head.block_number() {
+            return self;
+        }
+
+        if block.hash() < head.hash() {
+            ChainOrdering::Superior
+        } else {
+            ChainOrdering::Inferior
+        }
+    }
+
+    /// Same as `order_chains`, but additionally returns the fork height and the first skip block
+    /// on either chain that was found while comparing them.
+    pub fn analyze_chains<B: AbstractBlockchain, F, G>(
+        blockchain: &B,
+        block: &Block,
+        prev_info: &ChainInfo,
+        get_chain_info: F,
+        get_block_at: G,
+    ) -> ChainAnalysis
     where
         F: Fn(&Blake2bHash) -> Result<ChainInfo, BlockchainError>,
         G: Fn(u32) -> Result<Block, BlockchainError>,
     {
         let mut chain_order = ChainOrdering::Unknown;
+        let mut fork_height = None;
+        let mut our_skip_block = None;
+        let mut their_skip_block = None;
 
         if block.parent_hash() == &blockchain.head_hash() {
             chain_order = ChainOrdering::Extend;
@@ -84,6 +149,7 @@ impl ChainOrdering {
             //    \- [0] - [1]
             let current_height = current.head.block_number();
             let min_height = cmp::min(blockchain.block_number(), block.block_number());
+            fork_height = Some(current_height - 1);
 
             // Iterate over common block heights starting from right after the intersection.
             for h in current_height..=min_height {
@@ -93,6 +159,13 @@ impl ChainOrdering {
                 let current_on_main_chain = get_block_at(h)
                     .expect("Corrupted store: Failed to find main chain equivalent of fork");
 
+                if current_block.is_skip() {
+                    their_skip_block.get_or_insert(h);
+                }
+                if current_on_main_chain.is_skip() {
+                    our_skip_block.get_or_insert(h);
+                }
+
                 if current_block.is_skip() && !current_on_main_chain.is_skip() {
                     chain_order = ChainOrdering::Superior;
                     break;
@@ -118,6 +191,11 @@ impl ChainOrdering {
             );
         }
 
-        chain_order
+        ChainAnalysis {
+            ordering: chain_order,
+            fork_height,
+            our_skip_block,
+            their_skip_block,
+        }
     }
 }
@@ -1,6 +1,6 @@
 use nimiq_block::{Block, BlockError, EquivocationProofError, ForkProof};
 use nimiq_hash::Blake2bHash;
-use nimiq_primitives::{account::AccountError, networks::NetworkId};
+use nimiq_primitives::{account::AccountError, coin::Coin, networks::NetworkId};
 use nimiq_transaction::EquivocationLocator;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -15,10 +15,18 @@ pub enum ForkEvent {
 pub enum BlockchainEvent {
     Extended(Blake2bHash),
     HistoryAdopted(Blake2bHash),
+    /// Carries the reverted and the adopted blocks. The adopted blocks are empty if the chain was
+    /// rolled back, in which case the new head is the parent of the first reverted block.
     Rebranched(Vec<(Blake2bHash, Block)>, Vec<(Blake2bHash, Block)>),
+    /// Sent right after `Rebranched`. Carries the cumulative transaction fees of the blocks that
+    /// were reverted by the rebranch.
+    RebranchedWithFees(Coin),
     /// Given Block was stored in the chain store but was not adopted as new head block.
     /// I.e. forked blocks and inferior chain blocks.
     Stored(Block),
+    /// Given Block was ignored because it is on an inferior chain. Carries the hash and the
+    /// block number of the ignored block.
+    Ignored(Blake2bHash, u32),
     Finalized(Blake2bHash),
     EpochFinalized(Blake2bHash),
 }
@@ -31,6 +39,8 @@ pub enum BlockchainError {
     FailedLoadingMainChain,
     #[error("Inconsistent chain/accounts state. Reset your consensus database.")]
     InconsistentState,
+    #[error("Genesis accounts don't match the genesis block's state root. Verify your genesis configuration.")]
+    GenesisAccountsMismatch,
     #[error("No network for: {:?}", _0)]
     NoNetwork(NetworkId),
     #[error("Block not found")]
@@ -39,12 +49,18 @@ pub enum BlockchainError {
     BlockBodyNotFound,
     #[error("Block is not a macro block")]
     BlockIsNotMacro,
+    #[error("Block is not an election block")]
+    BlockIsNotElection,
+    #[error("Block is not on the main chain")]
+    BlockNotOnMainChain,
     #[error("No validators found")]
     NoValidatorsFound,
     #[error("Invalid epoch ID")]
     InvalidEpoch,
     #[error("Accounts diff not found")]
     AccountsDiffNotFound,
+    #[error("The account at the staking contract address is not a staking contract")]
+    MissingStakingContract,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -56,6 +72,20 @@ pub enum PushResult {
     Ignored,
 }
 
+/// The outcome of pushing a block, together with information about the pushed block and the
+/// resulting state of the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushInfo {
+    /// The result of the push.
+    pub result: PushResult,
+    /// Whether the pushed block is a macro block.
+    pub is_macro: bool,
+    /// Whether the pushed block is an election block.
+    pub is_election: bool,
+    /// The epoch of the next block on top of the head, after the push.
+    pub epoch: u32,
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum PushError {
     #[error("Orphan block")]
@@ -3,7 +3,7 @@ pub use chain_info::ChainInfo;
 pub use chain_ordering::*;
 pub use error::{
     BlockchainError, BlockchainEvent, ChunksPushError, ChunksPushResult, Direction, ForkEvent,
-    PushError, PushResult,
+    PushError, PushInfo, PushResult,
 };
 
 mod abstract_blockchain;
@@ -5,17 +5,31 @@ use nimiq_block::{
 };
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_bls::KeyPair as BlsKeyPair;
-use nimiq_database::{traits::WriteTransaction, TransactionProxy as DBTransaction};
+use nimiq_database::TransactionProxy as DBTransaction;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash};
 use nimiq_keys::KeyPair as SchnorrKeyPair;
 use nimiq_primitives::policy::Policy;
 use nimiq_transaction::{
-    historic_transaction::HistoricTransaction, inherent::Inherent, Transaction,
+    historic_transaction::HistoricTransaction, inherent::Inherent, ExecutedTransaction, Transaction,
 };
 use rand::{CryptoRng, Rng, RngCore};
 
 use crate::Blockchain;
 
+/// Computes the body root of a micro block with the given equivocation proofs and executed
+/// transactions. This is the same `body_root` that `BlockProducer::next_micro_block` puts into
+/// the header, so it allows building micro headers externally.
+pub fn micro_body_root(
+    equivocation_proofs: &[EquivocationProof],
+    transactions: &[ExecutedTransaction],
+) -> Blake2sHash {
+    MicroBody {
+        equivocation_proofs: equivocation_proofs.to_vec(),
+        transactions: transactions.to_vec(),
+    }
+    .hash()
+}
+
 /// Struct that contains all necessary information to actually produce blocks.
 /// It has the validator keys for this validator.
 #[derive(Clone)]
@@ -33,6 +47,19 @@ impl BlockProducer {
         }
     }
 
+    /// Creates the next micro block without any transactions, equivocation proofs or extra data.
+    /// Its timestamp is the earliest regular one after the head.
+    pub fn empty_micro_block(&self, blockchain: &Blockchain) -> MicroBlock {
+        self.next_micro_block(
+            blockchain,
+            blockchain.head().timestamp() + Policy::BLOCK_SEPARATION_TIME,
+            vec![],
+            vec![],
+            vec![],
+            None,
+        )
+    }
+
     /// Creates the next micro block.
     pub fn next_micro_block(
         &self,
@@ -145,19 +172,11 @@ impl BlockProducer {
                 .collect(),
         );
 
-        // Store the historic transactions into the history tree and calculate the history root.
-        let mut txn = blockchain.write_transaction();
-
-        let (history_root, _) = blockchain
-            .history_store
-            .add_to_history(&mut txn, block_number, &hist_txs)
+        // Calculate the history root from the historic transactions.
+        let history_root = blockchain
+            .compute_history_root(block_number, &hist_txs)
             .expect("Failed to compute history root during block production.");
 
-        // Not strictly necessary to drop the lock here, but sign as well as compress might be somewhat expensive
-        // and there is no need to hold the lock after this point.
-        // Abort txn so that blockchain is no longer borrowed.
-        txn.abort();
-
         // Create the micro block body.
         let body = MicroBody {
             equivocation_proofs,
@@ -322,16 +341,11 @@ impl BlockProducer {
             vec![],
         );
 
-        // Store the historic transactions into the history tree and calculate the history root.
-        let mut txn = blockchain.write_transaction();
-
+        // Calculate the history root from the historic transactions.
         macro_block.header.history_root = blockchain
-            .history_store
-            .add_to_history(&mut txn, block_number, &hist_txs)
-            .expect("Failed to compute history root during block production.")
-            .0;
+            .compute_history_root(block_number, &hist_txs)
+            .expect("Failed to compute history root during block production.");
 
-        txn.abort();
         macro_block
     }
 
@@ -359,7 +373,11 @@ impl BlockProducer {
 
         // If this is an election block, calculate the validator set for the next epoch.
         let validators = match Policy::is_election_block_at(macro_header.block_number) {
-            true => Some(blockchain.next_validators(&macro_header.seed)),
+            true => Some(
+                blockchain
+                    .next_validators(&macro_header.seed)
+                    .expect("Staking Contract must be present to select the validators"),
+            ),
             false => None,
         };
 
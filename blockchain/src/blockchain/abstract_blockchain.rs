@@ -48,11 +48,11 @@ impl AbstractBlockchain for Blockchain {
     }
 
     fn current_validators(&self) -> Option<Validators> {
-        self.state.current_slots.clone()
+        self.state.current_slots.as_deref().cloned()
     }
 
     fn previous_validators(&self) -> Option<Validators> {
-        self.state.previous_slots.clone()
+        self.state.previous_slots.as_deref().cloned()
     }
 
     fn contains(&self, hash: &Blake2bHash, include_forks: bool) -> bool {
@@ -2,15 +2,21 @@ use nimiq_account::{
     Account, Accounts, BlockLogger, BlockState, RevertInfo, TransactionOperationReceipt,
 };
 use nimiq_block::{Block, BlockError, SkipBlockInfo};
-use nimiq_blockchain_interface::PushError;
-use nimiq_database::{traits::Database, TransactionProxy};
+use nimiq_blockchain_interface::{BlockchainError, PushError};
+use nimiq_database::{traits::Database, volatile::VolatileDatabase, TransactionProxy};
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_primitives::{
     key_nibbles::KeyNibbles,
-    trie::{error::IncompleteTrie, trie_diff::TrieDiff, trie_proof::TrieProof},
+    trie::{
+        error::IncompleteTrie,
+        trie_chunk::{TrieChunk, TrieChunkPushResult},
+        trie_diff::TrieDiff,
+        trie_proof::TrieProof,
+    },
 };
 use nimiq_serde::Deserialize;
-use nimiq_trie::WriteTransactionProxy;
+use nimiq_trie::{trie::MerkleRadixTrie, WriteTransactionProxy};
 
 use crate::Blockchain;
 
@@ -25,6 +31,46 @@ pub struct AccountsChunk {
 
 /// Implements methods to handle the accounts.
 impl Blockchain {
+    /// Verifies that the accounts tree and the chain store are consistent with the blockchain
+    /// state. This performs the same checks as when loading the blockchain, so it can be used to
+    /// detect a corrupted database (e.g. after a crash) without restarting:
+    /// - the accounts root hash must match the state root of the head block, unless the accounts
+    ///   tree is incomplete,
+    /// - the macro head hash must resolve to a macro block,
+    /// - the election head hash must resolve to an election block.
+    pub fn verify_state_consistency(&self) -> Result<(), BlockchainError> {
+        let txn = self.read_transaction();
+
+        if let Some(accounts_hash) = self.state.accounts.get_root_hash(Some(&txn)) {
+            if self.state.main_chain.head.state_root() != &accounts_hash {
+                error!(
+                    head_state_root = %self.state.main_chain.head.state_root(),
+                    %accounts_hash,
+                    "Main chain's head state root doesn't match the accounts tree"
+                );
+                return Err(BlockchainError::InconsistentState);
+            }
+        }
+
+        match self
+            .chain_store
+            .get_block(&self.state.macro_head_hash, false, Some(&txn))
+        {
+            Ok(Block::Macro(_)) => {}
+            _ => return Err(BlockchainError::InconsistentState),
+        }
+
+        match self
+            .chain_store
+            .get_block(&self.state.election_head_hash, false, Some(&txn))
+        {
+            Ok(Block::Macro(block)) if block.is_election() => {}
+            _ => return Err(BlockchainError::InconsistentState),
+        }
+
+        Ok(())
+    }
+
     /// Updates the accounts given a block.
     /// Expects a full block with body.
     pub fn commit_accounts(
@@ -126,6 +172,17 @@ impl Blockchain {
                     &revert_info,
                 );
 
+                // Prune revert infos of blocks that can't be reverted anymore because of the
+                // maximum rebranch depth.
+                let prune_height = self
+                    .config
+                    .max_rebranch_depth
+                    .filter(|_| self.config.prune_revert_infos)
+                    .and_then(|depth| micro_block.header.block_number.checked_sub(depth));
+                if let Some(prune_height) = prune_height {
+                    self.chain_store.prune_revert_infos(txn.raw(), prune_height);
+                }
+
                 let total_tx_size = self
                     .history_store
                     .add_block(txn.raw(), block, inherents)
@@ -207,6 +264,45 @@ impl Blockchain {
         Ok(total_size)
     }
 
+    /// Computes the state root that results from applying the given block, including the inherents
+    /// derived from its equivocation proofs, skip block info or macro block rewards, on top of the
+    /// current head. Nothing is committed. Expects a full block with body that is a successor of
+    /// the head.
+    pub fn compute_state_root_for(&self, block: &Block) -> Result<Blake2bHash, PushError> {
+        let accounts = &self.state.accounts;
+        if !accounts.is_complete(None) {
+            return Err(PushError::IncompleteAccountsTrie);
+        }
+
+        let block_state = BlockState::new(block.block_number(), block.timestamp());
+
+        let (transactions, inherents) = match block {
+            Block::Macro(ref macro_block) => {
+                (vec![], self.create_macro_block_inherents(macro_block))
+            }
+            Block::Micro(ref micro_block) => {
+                let body = micro_block
+                    .body
+                    .as_ref()
+                    .ok_or(PushError::InvalidBlock(BlockError::MissingBody))?;
+
+                let inherents = self.create_punishment_inherents(
+                    block_state.number,
+                    &body.equivocation_proofs,
+                    SkipBlockInfo::from_micro_block(micro_block),
+                    None,
+                );
+
+                (body.get_raw_transactions(), inherents)
+            }
+        };
+
+        let (state_root, _, _) =
+            accounts.exercise_transactions(&transactions, &inherents, &block_state)?;
+
+        Ok(state_root)
+    }
+
     /// Produces a Merkle proof of the inclusion of the given keys in the
     /// Merkle Radix Trie.
     pub fn get_accounts_proof(&self, keys: Vec<&KeyNibbles>) -> Result<TrieProof, IncompleteTrie> {
@@ -215,6 +311,52 @@ impl Blockchain {
         self.state.accounts.get_proof(Some(&txn), keys)
     }
 
+    /// Produces a Merkle proof of the inclusion of the given keys in the
+    /// Merkle Radix Trie at the state of the given block.
+    /// Since only the accounts tree of the current head is kept, this returns `None` for any
+    /// other block as well as when the accounts trie is incomplete.
+    pub fn get_accounts_proof_at(
+        &self,
+        block_hash: &Blake2bHash,
+        keys: Vec<&KeyNibbles>,
+    ) -> Option<TrieProof> {
+        if *block_hash != self.state.head_hash {
+            return None;
+        }
+
+        self.get_accounts_proof(keys).ok()
+    }
+
+    /// Verifies that the given chunks, in order, cover the whole accounts trie with the given root
+    /// hash without any gaps. The chunks are applied one after another to an empty trie in a
+    /// temporary database. This checks the proof of each chunk against the root and requires every
+    /// chunk to start where the previous one ended. The last chunk must complete the trie.
+    pub fn verify_accounts_chunks(chunks: &[TrieChunk], expected_root: &Blake2bHash) -> bool {
+        let env = VolatileDatabase::new(20).expect("Could not open a volatile database");
+        let trie = MerkleRadixTrie::new_incomplete(env.clone(), "AccountsChunks");
+        let mut raw_txn = env.write_transaction();
+        let mut txn: WriteTransactionProxy = (&mut raw_txn).into();
+
+        let mut start_key = Some(KeyNibbles::ROOT);
+        for chunk in chunks {
+            // The trie is already complete, so there must not be any further chunks.
+            let Some(start) = start_key else {
+                return false;
+            };
+
+            match trie.put_chunk(&mut txn, start, chunk.clone(), expected_root.clone()) {
+                Ok(TrieChunkPushResult::Applied) => {}
+                Ok(TrieChunkPushResult::Ignored) | Err(_) => return false,
+            }
+
+            start_key = chunk.end_key.clone();
+        }
+
+        start_key.is_none()
+            && trie.is_complete(&txn)
+            && trie.root_hash(&txn).as_ref() == Some(expected_root)
+    }
+
     /// Gets an accounts chunk given a start key and a limit
     pub fn get_accounts_chunk(
         &self,
@@ -1,10 +1,11 @@
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
 use nimiq_account::{Accounts, BlockLog};
 use nimiq_block::Block;
 use nimiq_blockchain_interface::{BlockchainError, BlockchainEvent, ChainInfo, ForkEvent};
 use nimiq_database::{
     traits::{Database, WriteTransaction},
+    volatile::VolatileDatabase,
     DatabaseProxy, TransactionProxy, WriteTransactionProxy,
 };
 use nimiq_genesis::NetworkInfo;
@@ -12,19 +13,34 @@ use nimiq_hash::Blake2bHash;
 use nimiq_primitives::{
     coin::Coin, networks::NetworkId, policy::Policy, slots_allocation::Validators, trie::TrieItem,
 };
+use nimiq_transaction::Transaction;
 use nimiq_utils::time::OffsetTime;
+use parking_lot::{Mutex, RwLock};
 use tokio::sync::broadcast::{channel as broadcast, Sender as BroadcastSender};
 
 #[cfg(feature = "metrics")]
 use crate::chain_metrics::BlockchainMetrics;
 use crate::{
-    blockchain_state::BlockchainState, chain_store::ChainStore, history::HistoryStore,
-    interface::HistoryInterface, light_history_store::LightHistoryStore,
-    reward::genesis_parameters,
+    blockchain::slots::SlotsCache,
+    blockchain_state::BlockchainState,
+    chain_store::ChainStore,
+    history::HistoryStore,
+    interface::HistoryInterface,
+    light_history_store::LightHistoryStore,
+    reward::{genesis_parameters, RewardFallback},
 };
 
 const BROADCAST_MAX_CAPACITY: usize = 256;
 
+/// Computes the state root of the given genesis accounts in a temporary database.
+fn genesis_accounts_root(genesis_accounts: Vec<TrieItem>) -> Blake2bHash {
+    let env = VolatileDatabase::new(20).expect("Could not open a volatile database");
+    let accounts = Accounts::new(env.clone());
+    let mut txn = env.write_transaction();
+    accounts.init(&mut (&mut txn).into(), genesis_accounts);
+    accounts.get_root_hash_assert(Some(&txn))
+}
+
 /// The Blockchain struct. It stores all information of the blockchain. It is the main data
 /// structure in this crate.
 pub struct Blockchain {
@@ -50,6 +66,13 @@ pub struct Blockchain {
     pub state: BlockchainState,
     /// A reference to a "function" to test whether a given transaction is known and valid.
     pub tx_verification_cache: Arc<dyn TransactionVerificationCache>,
+    /// An optional filter that blocks must pass before they are accepted. Blocks containing a
+    /// transaction for which the filter returns `false` are rejected.
+    pub(crate) transaction_filter: RwLock<Option<Box<TransactionFilter>>>,
+    /// A cache of the validators of past epochs, so that repeated slot lookups for the same epoch
+    /// don't need to read and deserialize its election block every time. The validators of the
+    /// current and previous epoch are kept in the state instead.
+    pub(crate) slots_cache: Mutex<SlotsCache>,
     /// The metrics for the blockchain. Needed for analysis.
     #[cfg(feature = "metrics")]
     pub(crate) metrics: Arc<BlockchainMetrics>,
@@ -73,6 +96,37 @@ pub struct BlockchainConfig {
     /// The history store that is used by the full blockchain.
     /// If this is set to true, the light history store is used.
     pub light_history_store: bool,
+    /// Flag indicating if the validators and reward transactions in macro bodies should be trusted
+    /// instead of being recomputed from the state. This must only be enabled when syncing from a
+    /// fully trusted source.
+    pub trust_macro_bodies: bool,
+    /// Maximum number of main chain blocks that may be reverted in a single rebranch. Forks that
+    /// would revert more blocks are rejected. `None` means there is no limit.
+    pub max_rebranch_depth: Option<u32>,
+    /// Flag indicating if revert infos of micro blocks that are deeper than `max_rebranch_depth`
+    /// should be pruned, as they can't be reverted anymore. Has no effect if there is no maximum
+    /// rebranch depth.
+    pub prune_revert_infos: bool,
+    /// Maximum time (in milliseconds) that a block's timestamp may be ahead of our local time.
+    /// Blocks further in the future are rejected. If `None`, which is the default, the timestamp
+    /// is not checked against the local time.
+    pub max_timestamp_drift: Option<u64>,
+    /// Flag indicating if blocks that are already known are verified again when they are pushed,
+    /// instead of being reported as known right away. A known block that fails the verification
+    /// indicates a corrupted store. This is meant for auditing and is disabled by default.
+    pub audit_known_blocks: bool,
+    /// What happens to a batch reward if the validator's reward address can't accept it. This
+    /// changes the reward transactions of macro blocks, so all nodes of a network must use the
+    /// same setting. Anything other than `RewardFallback::Burn` is only meant for private networks.
+    pub reward_fallback: RewardFallback,
+    /// Flag indicating if forks of the same height whose ordering is otherwise unknown are
+    /// ordered by the hash of their heads, so that nodes seeing both forks converge on the same
+    /// one. This changes the fork choice, so all nodes of a network must use the same setting.
+    pub tie_break_forks: bool,
+    /// Maximum number of main chain blocks whose events are replayed by
+    /// `Blockchain::notifier_as_stream_from`. Listeners that are further behind are asked to
+    /// resync instead.
+    pub max_replayed_blocks: u32,
 }
 
 impl Default for BlockchainConfig {
@@ -81,6 +135,14 @@ impl Default for BlockchainConfig {
             keep_history: true,
             max_epochs_stored: Policy::MIN_EPOCHS_STORED,
             light_history_store: false,
+            trust_macro_bodies: false,
+            max_rebranch_depth: None,
+            prune_revert_infos: false,
+            max_timestamp_drift: None,
+            audit_known_blocks: false,
+            reward_fallback: RewardFallback::Burn,
+            tie_break_forks: false,
+            max_replayed_blocks: 1024,
         }
     }
 }
@@ -108,6 +170,25 @@ impl Blockchain {
         )
     }
 
+    /// Creates a new blockchain from the given genesis block and accounts instead of the genesis
+    /// of the network, using the default configuration. This is meant for devnets and local
+    /// testing with custom genesis states.
+    pub fn new_with_genesis(
+        env: DatabaseProxy,
+        network_id: NetworkId,
+        genesis_block: Block,
+        genesis_accounts: Vec<TrieItem>,
+    ) -> Result<Self, BlockchainError> {
+        Self::with_genesis(
+            env,
+            BlockchainConfig::default(),
+            Arc::new(OffsetTime::new()),
+            network_id,
+            genesis_block,
+            genesis_accounts,
+        )
+    }
+
     /// Creates a new blockchain with the given genesis block.
     pub fn with_genesis(
         env: DatabaseProxy,
@@ -147,6 +228,7 @@ impl Blockchain {
                 time,
                 network_id,
                 genesis_block,
+                genesis_accounts,
                 head_hash,
             )?,
             None => Blockchain::init(
@@ -170,6 +252,7 @@ impl Blockchain {
         time: Arc<OffsetTime>,
         network_id: NetworkId,
         genesis_block: Block,
+        genesis_accounts: Vec<TrieItem>,
         head_hash: Blake2bHash,
     ) -> Result<Self, BlockchainError> {
         // Check that the correct genesis block is stored.
@@ -185,6 +268,7 @@ impl Blockchain {
         let genesis_block_number = genesis_block.block_number();
         let genesis_hash = genesis_block.hash();
 
+        let genesis_state_root = genesis_block.state_root().clone();
         let (genesis_supply, genesis_timestamp) =
             genesis_parameters(&genesis_block.unwrap_macro().header);
 
@@ -196,8 +280,30 @@ impl Blockchain {
         // Check that chain/accounts state is consistent.
         let accounts = Accounts::new(env.clone());
 
+        let accounts_hash = accounts.get_root_hash(None);
+        let state_mismatch = accounts_hash
+            .as_ref()
+            .is_some_and(|accounts_hash| main_chain.head.state_root() != accounts_hash);
+
+        // Check that the genesis accounts match the genesis block. Building the genesis accounts
+        // is expensive, so this is only done if the head is still the genesis block or if the
+        // accounts state is inconsistent, to distinguish a wrong genesis configuration from a
+        // corrupted accounts state.
+        if state_mismatch || head_hash == genesis_hash {
+            let genesis_accounts_hash = genesis_accounts_root(genesis_accounts);
+            if genesis_state_root != genesis_accounts_hash {
+                error!(
+                    %genesis_state_root,
+                    %genesis_accounts_hash,
+                    reason = "Genesis accounts don't match the genesis block",
+                    "Failed to load blockchain"
+                );
+                return Err(BlockchainError::GenesisAccountsMismatch);
+            }
+        }
+
         // Verify accounts hash if the tree is complete or changes only happened in the complete part.
-        if let Some(accounts_hash) = accounts.get_root_hash(None) {
+        if let Some(accounts_hash) = accounts_hash {
             if main_chain.head.state_root() != &accounts_hash {
                 log::error!(
                     "Main chain's head state root: {:?}, Account state root: {:?}",
@@ -293,10 +399,12 @@ impl Blockchain {
                 macro_head_hash,
                 election_head,
                 election_head_hash,
-                current_slots: Some(current_slots),
-                previous_slots: last_slots,
+                current_slots: Some(Arc::new(current_slots)),
+                previous_slots: last_slots.map(Arc::new),
             },
             tx_verification_cache: Arc::new(DEFAULT_TX_VERIFICATION_CACHE),
+            transaction_filter: RwLock::new(None),
+            slots_cache: Mutex::new(VecDeque::new()),
             #[cfg(feature = "metrics")]
             metrics: Arc::new(BlockchainMetrics::default()),
             genesis_supply,
@@ -333,6 +441,19 @@ impl Blockchain {
         let mut txn = env.write_transaction();
         accounts.init(&mut (&mut txn).into(), genesis_accounts);
 
+        // Check that the committed genesis accounts match the genesis block.
+        let accounts_hash = accounts.get_root_hash_assert(Some(&txn));
+        if main_chain.head.state_root() != &accounts_hash {
+            error!(
+                genesis_state_root = %main_chain.head.state_root(),
+                genesis_accounts_hash = %accounts_hash,
+                reason = "Genesis accounts don't match the genesis block",
+                "Failed to initialize blockchain"
+            );
+            txn.abort();
+            return Err(BlockchainError::GenesisAccountsMismatch);
+        }
+
         // Store genesis block.
         chain_store.put_chain_info(&mut txn, &head_hash, &main_chain, true);
         chain_store.set_head(&mut txn, &head_hash);
@@ -368,10 +489,12 @@ impl Blockchain {
                 macro_head_hash: head_hash.clone(),
                 election_head: genesis_macro_block,
                 election_head_hash: head_hash,
-                current_slots: Some(current_slots),
-                previous_slots: Some(Validators::default()),
+                current_slots: Some(Arc::new(current_slots)),
+                previous_slots: Some(Arc::new(Validators::default())),
             },
             tx_verification_cache: Arc::new(DEFAULT_TX_VERIFICATION_CACHE),
+            transaction_filter: RwLock::new(None),
+            slots_cache: Mutex::new(VecDeque::new()),
             #[cfg(feature = "metrics")]
             metrics: Arc::new(BlockchainMetrics::default()),
             genesis_supply,
@@ -381,6 +504,12 @@ impl Blockchain {
         })
     }
 
+    /// Sets a filter that every transaction of a block must pass for the block to be accepted.
+    /// By default, no filter is set and all transactions are accepted.
+    pub fn set_transaction_filter(&self, filter: Box<TransactionFilter>) {
+        *self.transaction_filter.write() = Some(filter);
+    }
+
     pub fn get_genesis_parameters(&self) -> (Coin, u64) {
         (self.genesis_supply, self.genesis_timestamp)
     }
@@ -398,6 +527,9 @@ impl Blockchain {
     }
 }
 
+/// A predicate deciding whether a transaction is allowed in a block.
+pub type TransactionFilter = dyn Fn(&Transaction) -> bool + Send + Sync;
+
 pub trait TransactionVerificationCache: Send + Sync {
     fn is_known(&self, tx_hash: &Blake2bHash) -> bool;
 }
@@ -1,4 +1,4 @@
-use std::{collections::BTreeMap, error::Error};
+use std::{collections::BTreeMap, error::Error, sync::Arc};
 
 use nimiq_account::{BlockLogger, BlockState};
 use nimiq_block::{Block, BlockError};
@@ -393,6 +393,9 @@ impl Blockchain {
                 %wanted_history_root,
                 "Rejecting block",
             );
+            txn.abort();
+            #[cfg(feature = "metrics")]
+            this.metrics.note_invalid_block();
             return Err(PushError::InvalidBlock(BlockError::InvalidHistoryRoot));
         }
 
@@ -428,7 +431,8 @@ impl Blockchain {
             this.state.election_head = macro_block.clone();
             this.state.election_head_hash = block_hash.clone();
             this.state.previous_slots = this.state.current_slots.take();
-            this.state.current_slots = macro_block.get_validators();
+            this.state.current_slots = macro_block.get_validators().map(Arc::new);
+            this.invalidate_slots_cache();
         }
 
         let this = RwLockWriteGuard::downgrade_to_upgradable(this);
@@ -1,7 +1,8 @@
 use nimiq_account::StakingContract;
-use nimiq_block::{EquivocationProof, MacroBlock, MacroHeader, SkipBlockInfo};
+use nimiq_block::{Block, EquivocationProof, MacroBlock, MacroHeader, SkipBlockInfo};
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_database as db;
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_primitives::{
     account::AccountType,
@@ -10,9 +11,12 @@ use nimiq_primitives::{
     slots_allocation::{JailedValidator, PenalizedSlot},
 };
 use nimiq_transaction::{inherent::Inherent, reward::RewardTransaction};
-use nimiq_vrf::{DiscreteDistribution, VrfUseCase};
+use nimiq_vrf::{DiscreteDistribution, VrfSeed, VrfUseCase};
 
-use crate::{reward::block_reward_for_batch, Blockchain};
+use crate::{
+    reward::{block_reward_for_batch, BatchRewardSummary, RewardFallback},
+    Blockchain,
+};
 
 /// Implements methods that create inherents.
 impl Blockchain {
@@ -31,6 +35,31 @@ impl Blockchain {
 
         inherents
     }
+
+    /// Computes the inherents of a prospective macro block with the given seed, block number and
+    /// timestamp. These are the only header fields that the inherents depend on, so the result is
+    /// the same as `create_macro_block_inherents` for a macro block with that header and without
+    /// a body.
+    pub fn compute_macro_block_inherents(
+        &self,
+        seed: VrfSeed,
+        block_number: u32,
+        timestamp: u64,
+    ) -> Vec<Inherent> {
+        let macro_block = MacroBlock {
+            header: MacroHeader {
+                block_number,
+                timestamp,
+                seed,
+                ..Default::default()
+            },
+            body: None,
+            justification: None,
+        };
+
+        self.create_macro_block_inherents(&macro_block)
+    }
+
     /// Given equivocation proofs and (or) a skip block, it returns the respective punishment inherents. It expects
     /// verified equivocation proofs and (or) skip block.
     pub fn create_punishment_inherents(
@@ -269,17 +298,39 @@ impl Blockchain {
                     .get_validator(&data_store.read(&txn), &validator_slot.address)
                     .expect("Couldn't find validator in the accounts trie when paying rewards!");
 
-                let tx = RewardTransaction {
+                let mut tx = RewardTransaction {
                     validator_address: validator.address.clone(),
                     recipient: validator.reward_address.clone(),
                     value: reward,
                 };
 
                 // Test whether account will accept inherent. If it can't then the reward will be
-                // burned.
+                // burned, unless the fallback to the validator address is enabled and that one
+                // accepts it.
                 // TODO Improve this check: it assumes that only BasicAccounts can receive transactions.
-                let account = self.state.accounts.get_complete(&tx.recipient, Some(&txn));
-                if account.account_type() == AccountType::Basic {
+                let accepts = |address: &Address| {
+                    self.state
+                        .accounts
+                        .get_complete(address, Some(&txn))
+                        .account_type()
+                        == AccountType::Basic
+                };
+                let mut accepted = accepts(&tx.recipient);
+                if !accepted
+                    && self.config.reward_fallback == RewardFallback::ValidatorAddress
+                    && accepts(&validator.address)
+                {
+                    debug!(
+                        target_address = %tx.recipient,
+                        fallback_address = %validator.address,
+                        reward = %tx.value,
+                        "Paying batch reward to validator address"
+                    );
+                    tx.recipient = validator.address.clone();
+                    accepted = true;
+                }
+
+                if accepted {
                     num_eligible_slots_for_accepted_tx.push(num_eligible_slots);
                     transactions.push(tx);
                 } else {
@@ -324,6 +375,173 @@ impl Blockchain {
         transactions
     }
 
+    /// Returns an estimate of the reward pot accumulated by the batch in progress, i.e. the block
+    /// reward projected to the timestamp of the current head plus the transaction fees collected
+    /// so far in the batch. The figure is only an estimate: the final reward depends on the
+    /// timestamp of the macro block that finalizes the batch.
+    /// Returns zero if the head is a macro block, since the next batch hasn't started yet.
+    pub fn current_reward_pot(&self) -> Coin {
+        let head = &self.state.main_chain.head;
+        if head.is_macro() {
+            return Coin::ZERO;
+        }
+
+        // Project the reward to a macro block finalizing the batch at the current head.
+        let projected_header = MacroHeader {
+            block_number: Policy::macro_block_of(Policy::batch_at(head.block_number()))
+                .expect("Batch of the head must have a macro block"),
+            timestamp: head.timestamp(),
+            ..Default::default()
+        };
+
+        let block_reward = block_reward_for_batch(
+            &projected_header,
+            &self.state.macro_info.head.unwrap_macro_ref().header,
+            self.genesis_supply,
+            self.genesis_timestamp,
+        );
+
+        block_reward + self.state.main_chain.cum_tx_fees
+    }
+
+    /// Returns a summary of the rewards that were distributed by the macro block of the given
+    /// batch. The summary is read from the reward transactions committed in the macro body, so
+    /// the amounts match exactly what was paid out.
+    /// Returns `None` for batch 0, for batches that are not finalized yet and if the macro body
+    /// is not available.
+    pub fn compute_batch_rewards(&self, batch: u32) -> Option<BatchRewardSummary> {
+        if batch == 0 {
+            return None;
+        }
+
+        let block_number = Policy::macro_block_of(batch)?;
+        if block_number > self.macro_head().block_number() {
+            return None;
+        }
+
+        let transactions = self
+            .chain_store
+            .get_block_at(block_number, true, None)
+            .ok()?
+            .unwrap_macro()
+            .body?
+            .transactions;
+
+        let mut reward_pot = Coin::ZERO;
+        let mut burned_reward = Coin::ZERO;
+        let mut validator_rewards = vec![];
+        for tx in transactions {
+            reward_pot += tx.value;
+            if tx.recipient == Address::burn_address() {
+                burned_reward += tx.value;
+            } else {
+                validator_rewards.push((tx.validator_address, tx.value));
+            }
+        }
+
+        Some(BatchRewardSummary {
+            reward_pot,
+            slot_reward: reward_pot / Policy::SLOTS as u64,
+            remainder: reward_pot % Policy::SLOTS as u64,
+            burned_reward,
+            validator_rewards,
+        })
+    }
+
+    /// Returns the seed that was used to sample the slot receiving the reward remainder in the
+    /// macro block of the given batch. This is the seed of the macro header, from which the RNG
+    /// for `VrfUseCase::RewardDistribution` is derived.
+    /// Returns `None` for batch 0, for batches that are not finalized yet and if the macro block
+    /// is not available.
+    pub fn reward_distribution_seed(&self, batch: u32) -> Option<VrfSeed> {
+        if batch == 0 {
+            return None;
+        }
+
+        let block_number = Policy::macro_block_of(batch)?;
+        if block_number > self.macro_head().block_number() {
+            return None;
+        }
+
+        let macro_block = self
+            .chain_store
+            .get_block_at(block_number, false, None)
+            .ok()?
+            .unwrap_macro();
+
+        Some(macro_block.header.seed)
+    }
+
+    /// Returns the rewards that were paid to the given reward address in each finalized epoch
+    /// between `from_epoch` and `to_epoch` (both inclusive). Epochs in which the address didn't
+    /// receive any reward are omitted. Epoch 0 and epochs that are not finalized yet are skipped.
+    /// The rewards are read from the reward transactions committed in the macro bodies of each
+    /// batch, since they can't be recomputed from the current state.
+    /// This is a read-heavy operation: it loads every macro block of the requested epochs.
+    pub fn validator_reward_history(
+        &self,
+        reward_address: &Address,
+        from_epoch: u32,
+        to_epoch: u32,
+    ) -> Vec<(u32, Coin)> {
+        let last_finalized_epoch = Policy::epoch_at(self.election_head().block_number());
+        let batches_per_epoch = Policy::batches_per_epoch() as u32;
+
+        (from_epoch.max(1)..=to_epoch.min(last_finalized_epoch))
+            .filter_map(|epoch| {
+                let mut total = Coin::ZERO;
+                let mut received = false;
+
+                for batch in (epoch - 1) * batches_per_epoch + 1..=epoch * batches_per_epoch {
+                    let transactions = self
+                        .chain_store
+                        .get_block_at(Policy::macro_block_of(batch)?, true, None)
+                        .ok()?
+                        .unwrap_macro()
+                        .body?
+                        .transactions;
+
+                    for tx in transactions {
+                        if tx.recipient == *reward_address {
+                            total += tx.value;
+                            received = true;
+                        }
+                    }
+                }
+
+                received.then_some((epoch, total))
+            })
+            .collect()
+    }
+
+    /// Returns the net change of the total supply (in Lunas) caused by the block with the given
+    /// hash. The reward inherents of a macro block mint their value, minus the rewards that are
+    /// burned. Micro blocks don't distribute rewards, so their delta is zero.
+    /// Returns `None` if the block or the body of the macro block is not available.
+    pub fn block_supply_delta(&self, hash: &Blake2bHash) -> Option<i128> {
+        let macro_block = match self.chain_store.get_block(hash, true, None).ok()? {
+            Block::Micro(_) => return Some(0),
+            Block::Macro(macro_block) => macro_block,
+        };
+
+        // Without a body, the reward inherents would be recreated from the current state.
+        macro_block.body.as_ref()?;
+
+        let mut minted = 0i128;
+        let mut burned = 0i128;
+        for inherent in self.finalize_previous_batch(&macro_block) {
+            if let Inherent::Reward { target, value, .. } = inherent {
+                let value = i128::from(u64::from(value));
+                minted += value;
+                if target == Address::burn_address() {
+                    burned += value;
+                }
+            }
+        }
+
+        Some(minted - burned)
+    }
+
     /// Creates the inherent to finalize an epoch. The inherent is for updating the StakingContract.
     pub fn finalize_previous_epoch(&self) -> Inherent {
         // Create the FinalizeEpoch inherent.
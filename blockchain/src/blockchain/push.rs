@@ -1,10 +1,10 @@
-use std::{cmp, error::Error, ops::Deref};
+use std::{cmp, error::Error, ops::Deref, sync::Arc};
 
 use nimiq_account::{BlockLog, BlockLogger};
-use nimiq_block::{Block, ForkProof, MicroBlock};
+use nimiq_block::{Block, ForkProof, MicroBlock, MicroJustification};
 use nimiq_blockchain_interface::{
-    AbstractBlockchain, BlockchainEvent, ChainInfo, ChainOrdering, ChunksPushError,
-    ChunksPushResult, ForkEvent, PushError, PushResult,
+    AbstractBlockchain, BlockClassification, BlockchainError, BlockchainEvent, ChainInfo,
+    ChainOrdering, ChunksPushError, ChunksPushResult, ForkEvent, PushError, PushInfo, PushResult,
 };
 use nimiq_database::{
     traits::{ReadTransaction, WriteTransaction},
@@ -13,6 +13,7 @@ use nimiq_database::{
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
 use nimiq_primitives::{
+    coin::Coin,
     policy::Policy,
     trie::{
         trie_chunk::{TrieChunkPushResult, TrieChunkWithStart},
@@ -25,6 +26,13 @@ use tokio::sync::broadcast::Sender as BroadcastSender;
 
 use crate::Blockchain;
 
+/// The outcome of pushing a single block, together with the lock guard it was pushed under so
+/// that further blocks can be pushed without releasing the lock in between.
+type GuardedPushResult<'a> = (
+    RwLockUpgradableReadGuard<'a, Blockchain>,
+    Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError>,
+);
+
 fn send_vec(log_notifier: &BroadcastSender<BlockLog>, logs: Vec<BlockLog>) {
     for log in logs {
         // The log notifier is for informational purposes only, thus may have no listeners.
@@ -46,7 +54,7 @@ impl Blockchain {
         trusted: bool,
         diff: Option<TrieDiff>,
         chunks: Vec<TrieChunkWithStart>,
-    ) -> Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError> {
+    ) -> GuardedPushResult {
         // Ignore all blocks that precede (or are at the same height) as the most recent accepted
         // macro block.
         let last_macro_block = Policy::last_macro_block(this.block_number());
@@ -57,7 +65,10 @@ impl Blockchain {
                 last_macro_block_no = last_macro_block,
                 "Ignoring block",
             );
-            return Ok((PushResult::Ignored, Ok(ChunksPushResult::EmptyChunks)));
+            return (
+                this,
+                Ok((PushResult::Ignored, Ok(ChunksPushResult::EmptyChunks))),
+            );
         }
 
         // TODO: We might want to pass this as argument to this method.
@@ -69,14 +80,44 @@ impl Blockchain {
             .get_chain_info(&block.hash(), false, Some(&read_txn))
             .is_ok()
         {
-            return Ok((PushResult::Known, Ok(ChunksPushResult::EmptyChunks)));
+            // In audit mode, the stored copy of a known block is verified again and compared to
+            // the pushed block to detect a corrupted store.
+            let result = if this.config.audit_known_blocks {
+                this.chain_store
+                    .get_block(&block.hash(), true, Some(&read_txn))
+                    .map_err(PushError::from)
+                    .and_then(|stored_block| {
+                        this.verify_known_block(&read_txn, &stored_block)?;
+                        if stored_block != block {
+                            return Err(PushError::BlockchainError(
+                                BlockchainError::InconsistentState,
+                            ));
+                        }
+                        Ok(())
+                    })
+            } else {
+                Ok(())
+            };
+            read_txn.close();
+
+            if let Err(e) = result {
+                warn!(%block, error = %e, reason = "Stored copy of known block failed audit", "Rejecting block");
+                return (this, Err(e));
+            }
+
+            return (
+                this,
+                Ok((PushResult::Known, Ok(ChunksPushResult::EmptyChunks))),
+            );
         }
 
         // Check if we have this block's parent.
-        let prev_info = this
-            .chain_store
-            .get_chain_info(block.parent_hash(), false, Some(&read_txn))
-            .map_err(|error| {
+        let prev_info =
+            this.chain_store
+                .get_chain_info(block.parent_hash(), false, Some(&read_txn));
+        let prev_info = match prev_info {
+            Ok(prev_info) => prev_info,
+            Err(error) => {
                 warn!(
                     %error,
                     %block,
@@ -84,13 +125,32 @@ impl Blockchain {
                     parent_block_hash = %block.parent_hash(),
                     "Rejecting block",
                 );
-                PushError::Orphan
-            })?;
+                read_txn.close();
+                return (this, Err(PushError::Orphan));
+            }
+        };
+
+        // Cheaply reject blocks on top of the head that replay transactions, before the expensive
+        // verification. The validity window reflects the main chain, so blocks on forks are only
+        // checked once they are applied.
+        if block.parent_hash() == &this.state.head_hash {
+            if let Some(tx_hash) = this.find_duplicate_transaction(&block, &read_txn) {
+                warn!(
+                    %block,
+                    reason = "transaction already included",
+                    transaction_hash = %tx_hash,
+                    "Rejecting block",
+                );
+                read_txn.close();
+                return (this, Err(PushError::DuplicateTransaction));
+            }
+        }
 
         // Verify the block.
         if let Err(e) = this.verify_block(&read_txn, &block, trusted) {
             warn!(%block, error = %e, reason = "Block verifications failed", "Rejecting block");
-            return Err(e);
+            read_txn.close();
+            return (this, Err(e));
         }
 
         // Detect forks in non-skip micro blocks.
@@ -108,13 +168,16 @@ impl Blockchain {
         }
 
         // Calculate chain ordering.
-        let chain_order = ChainOrdering::order_chains(
+        let mut chain_order = ChainOrdering::order_chains(
             this.deref(),
             &block,
             &prev_info,
             |hash| this.get_chain_info(hash, false, Some(&read_txn)),
             |height| this.get_block_at(height, false, Some(&read_txn)),
         );
+        if this.config.tie_break_forks {
+            chain_order = chain_order.tie_break(&block, &this.state.main_chain.head);
+        }
         let prev_missing_range = this.get_missing_accounts_range(Some(&read_txn));
 
         read_txn.close();
@@ -163,11 +226,65 @@ impl Blockchain {
 
         // Fork and inferior chain block fire a Stored Event.
         // They can never fire a Finalized or EpochFinalized as then they would not be inferior/forked.
+        let block_hash = chain_info.head.hash();
+        let block_number = chain_info.head.block_number();
         this.notifier
             .send(BlockchainEvent::Stored(chain_info.head))
             .ok();
 
-        Ok((result, Ok(ChunksPushResult::EmptyChunks)))
+        // Inferior chain blocks additionally fire an Ignored event.
+        if result == PushResult::Ignored {
+            this.notifier
+                .send(BlockchainEvent::Ignored(block_hash, block_number))
+                .ok();
+        }
+
+        (this, Ok((result, Ok(ChunksPushResult::EmptyChunks))))
+    }
+
+    /// Predicts the outcome of pushing the given block without committing anything. The block is
+    /// verified and its chain is ordered against the main chain, the same way `push` does.
+    pub fn classify_block(&self, block: &Block) -> Result<BlockClassification, PushError> {
+        if block.block_number() <= Policy::last_macro_block(self.block_number()) {
+            return Ok(BlockClassification::Inferior);
+        }
+
+        let read_txn = self.read_transaction();
+
+        if self
+            .chain_store
+            .get_chain_info(&block.hash(), false, Some(&read_txn))
+            .is_ok()
+        {
+            return Ok(BlockClassification::Known);
+        }
+
+        let Ok(prev_info) =
+            self.chain_store
+                .get_chain_info(block.parent_hash(), false, Some(&read_txn))
+        else {
+            return Ok(BlockClassification::Orphan);
+        };
+
+        self.verify_block(&read_txn, block, false)?;
+
+        let mut chain_order = ChainOrdering::order_chains(
+            self,
+            block,
+            &prev_info,
+            |hash| self.get_chain_info(hash, false, Some(&read_txn)),
+            |height| self.get_block_at(height, false, Some(&read_txn)),
+        );
+        if self.config.tie_break_forks {
+            chain_order = chain_order.tie_break(block, &self.state.main_chain.head);
+        }
+
+        Ok(match chain_order {
+            ChainOrdering::Extend => BlockClassification::Extend,
+            ChainOrdering::Superior => BlockClassification::Better,
+            ChainOrdering::Inferior => BlockClassification::Inferior,
+            ChainOrdering::Unknown => BlockClassification::Fork,
+        })
     }
 
     // To retain the option of having already taken a lock before this call the self was exchanged.
@@ -186,7 +303,33 @@ impl Blockchain {
             this.get_missing_accounts_range(None).is_none(),
             "Should call push only for complete tries"
         );
-        Self::push_wrapperfn(this, block, false, None, vec![]).map(|res| res.0)
+        Self::push_wrapperfn(this, block, false, None, vec![])
+            .1
+            .map(|res| res.0)
+    }
+
+    /// Pushes a block into the chain, like `push`, and additionally reports whether the block is a
+    /// macro or election block and which epoch the chain is in afterwards.
+    pub fn push_with_info(
+        this: RwLockUpgradableReadGuard<Self>,
+        block: Block,
+    ) -> Result<PushInfo, PushError> {
+        debug_assert!(
+            this.get_missing_accounts_range(None).is_none(),
+            "Should call push only for complete tries"
+        );
+        let is_macro = block.is_macro();
+        let is_election = block.is_election();
+
+        let (this, result) = Self::push_wrapperfn(this, block, false, None, vec![]);
+        let (result, _) = result?;
+
+        Ok(PushInfo {
+            result,
+            is_macro,
+            is_election,
+            epoch: Policy::epoch_at(this.block_number() + 1),
+        })
     }
 
     pub fn push_with_chunks(
@@ -195,7 +338,7 @@ impl Blockchain {
         diff: TrieDiff,
         chunks: Vec<TrieChunkWithStart>,
     ) -> Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError> {
-        Self::push_wrapperfn(this, block, false, Some(diff), chunks)
+        Self::push_wrapperfn(this, block, false, Some(diff), chunks).1
     }
 
     // To retain the option of having already taken a lock before this call the self was exchanged.
@@ -210,7 +353,37 @@ impl Blockchain {
         this: RwLockUpgradableReadGuard<Self>,
         block: Block,
     ) -> Result<PushResult, PushError> {
-        Self::push_wrapperfn(this, block, true, None, vec![]).map(|res| res.0)
+        Self::push_wrapperfn(this, block, true, None, vec![])
+            .1
+            .map(|res| res.0)
+    }
+
+    /// Pushes a batch of blocks into the chain, in order, while holding the lock for the whole batch.
+    /// Known, ignored and orphan blocks do not stop the batch, but any other error does.
+    /// Returns the results of all blocks processed so far. If the batch was stopped, the last result
+    /// is the error that stopped it.
+    pub fn push_blocks(
+        mut this: RwLockUpgradableReadGuard<Self>,
+        blocks: Vec<Block>,
+    ) -> Vec<Result<PushResult, PushError>> {
+        debug_assert!(
+            this.get_missing_accounts_range(None).is_none(),
+            "Should call push only for complete tries"
+        );
+
+        let mut results = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let (guard, result) = Self::push_wrapperfn(this, block, false, None, vec![]);
+            this = guard;
+
+            let result = result.map(|res| res.0);
+            let stop = !matches!(result, Ok(_) | Err(PushError::Orphan));
+            results.push(result);
+            if stop {
+                break;
+            }
+        }
+        results
     }
 
     /// Commits a set of chunks to the blockchain.
@@ -267,17 +440,16 @@ impl Blockchain {
         trust: bool,
         diff: Option<TrieDiff>,
         chunks: Vec<TrieChunkWithStart>,
-    ) -> Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError> {
+    ) -> GuardedPushResult {
         #[cfg(not(feature = "metrics"))]
         {
             Self::do_push(this, block, trust, diff, chunks)
         }
         #[cfg(feature = "metrics")]
         {
-            let metrics = this.metrics.clone();
-            let res = Self::do_push(this, block, trust, diff, chunks);
-            metrics.note_push_result(&res);
-            res
+            let (this, res) = Self::do_push(this, block, trust, diff, chunks);
+            this.metrics.note_push_result(&res);
+            (this, res)
         }
     }
 
@@ -289,7 +461,7 @@ impl Blockchain {
         mut prev_info: ChainInfo,
         diff: Option<TrieDiff>,
         chunks: Vec<TrieChunkWithStart>,
-    ) -> Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError> {
+    ) -> GuardedPushResult {
         let mut this = RwLockUpgradableReadGuard::upgrade(this);
         let mut txn = this.write_transaction();
 
@@ -303,7 +475,13 @@ impl Blockchain {
             chain_info.head.timestamp(),
         );
         let total_tx_size =
-            this.check_and_commit(&chain_info.head, diff, &mut txn, &mut block_logger)?;
+            match this.check_and_commit(&chain_info.head, diff, &mut txn, &mut block_logger) {
+                Ok(total_tx_size) => total_tx_size,
+                Err(e) => {
+                    txn.abort();
+                    return (RwLockWriteGuard::downgrade_to_upgradable(this), Err(e));
+                }
+            };
 
         chain_info.on_main_chain = true;
         chain_info.set_cumulative_hist_tx_size(&prev_info, total_tx_size);
@@ -349,7 +527,9 @@ impl Blockchain {
                 this.state.previous_slots.replace(old_slots);
 
                 let new_slots = macro_block.get_validators().unwrap();
-                this.state.current_slots.replace(new_slots);
+                this.state.current_slots.replace(Arc::new(new_slots));
+
+                this.invalidate_slots_cache();
             }
         }
 
@@ -392,7 +572,7 @@ impl Blockchain {
             .send(block_logger.build(total_tx_size))
             .ok();
 
-        Ok((PushResult::Extended, chunk_result))
+        (this, Ok((PushResult::Extended, chunk_result)))
     }
 
     /// Rebranches the current main chain.
@@ -402,14 +582,20 @@ impl Blockchain {
         chain_info: ChainInfo,
         diff: Option<TrieDiff>,
         chunks: Vec<TrieChunkWithStart>,
-    ) -> Result<(PushResult, Result<ChunksPushResult, ChunksPushError>), PushError> {
+    ) -> GuardedPushResult {
         let target_block = chain_info.head.to_string();
         debug!(block = target_block, "Rebranching");
         let mut this = RwLockUpgradableReadGuard::upgrade(this);
         let read_txn = this.read_transaction();
         // Find the common ancestor between our current main chain and the fork chain.
         let (mut ancestor, mut fork_chain) =
-            this.find_common_ancestor(block_hash, chain_info, diff, &read_txn)?;
+            match this.find_main_chain_ancestor(block_hash, chain_info, diff, &read_txn) {
+                Ok(r) => r,
+                Err(e) => {
+                    read_txn.close();
+                    return (RwLockWriteGuard::downgrade_to_upgradable(this), Err(e));
+                }
+            };
 
         read_txn.close();
 
@@ -420,6 +606,25 @@ impl Blockchain {
             "Found common ancestor",
         );
 
+        // Reject the fork if it would revert too many blocks of our main chain.
+        let rebranch_depth =
+            this.state.main_chain.head.block_number() - ancestor.1.head.block_number();
+        if let Some(max_rebranch_depth) = this.config.max_rebranch_depth {
+            if rebranch_depth > max_rebranch_depth {
+                warn!(
+                    block = target_block,
+                    rebranch_depth,
+                    max_rebranch_depth,
+                    reason = "rebranch too deep",
+                    "Rejecting fork"
+                );
+                return (
+                    RwLockWriteGuard::downgrade_to_upgradable(this),
+                    Err(PushError::InvalidFork),
+                );
+            }
+        }
+
         let mut write_txn = this.write_transaction();
         let (revert_chain, block_logs) =
             match this.rebranch_to(&mut fork_chain, &mut ancestor, &mut write_txn) {
@@ -441,7 +646,10 @@ impl Blockchain {
                     }
                     write_txn.commit();
 
-                    return Err(PushError::InvalidFork);
+                    return (
+                        RwLockWriteGuard::downgrade_to_upgradable(this),
+                        Err(PushError::InvalidFork),
+                    );
                 }
             };
 
@@ -463,7 +671,9 @@ impl Blockchain {
                 this.state.previous_slots.replace(old_slots);
 
                 let new_slots = macro_block.get_validators().unwrap();
-                this.state.current_slots.replace(new_slots);
+                this.state.current_slots.replace(Arc::new(new_slots));
+
+                this.invalidate_slots_cache();
             }
         }
 
@@ -476,6 +686,14 @@ impl Blockchain {
         // Try to apply any chunks we received.
         let chunk_result = this.commit_chunks(chunks, new_head_hash);
 
+        // The fee accumulator is reset after every macro block, which can only be the common
+        // ancestor since macro blocks are never reverted.
+        let reverted_fees = match revert_chain.first() {
+            Some((_, old_head_info)) if ancestor.1.head.is_macro() => old_head_info.cum_tx_fees,
+            Some((_, old_head_info)) => old_head_info.cum_tx_fees - ancestor.1.cum_tx_fees,
+            None => Coin::ZERO,
+        };
+
         let mut reverted_blocks = Vec::with_capacity(revert_chain.len());
         for (hash, chain_info) in revert_chain.into_iter().rev() {
             debug!(
@@ -505,12 +723,15 @@ impl Blockchain {
         );
         #[cfg(feature = "metrics")]
         this.metrics
-            .note_rebranch(&reverted_blocks, &adopted_blocks);
+            .note_rebranch(&reverted_blocks, &adopted_blocks, rebranch_depth);
 
         // We do not log errors if there are no listeners.
         this.notifier
             .send(BlockchainEvent::Rebranched(reverted_blocks, adopted_blocks))
             .ok();
+        this.notifier
+            .send(BlockchainEvent::RebranchedWithFees(reverted_fees))
+            .ok();
         if this.state.main_chain.head.is_election() {
             this.notifier
                 .send(BlockchainEvent::EpochFinalized(
@@ -525,7 +746,139 @@ impl Blockchain {
 
         send_vec(&this.log_notifier, block_logs);
 
-        Ok((PushResult::Rebranched, chunk_result))
+        (this, Ok((PushResult::Rebranched, chunk_result)))
+    }
+
+    /// Reverts the micro blocks of the main chain down to the given height, so that the block at
+    /// that height becomes the new head. Macro blocks are final, so a rollback beyond the last
+    /// macro block is rejected with `PushError::InvalidFork`, as is a rollback deeper than
+    /// `max_rebranch_depth` if revert infos are pruned. The reverted blocks are kept in the
+    /// store as fork blocks and are announced with a `BlockchainEvent::Rebranched` event without
+    /// any adopted blocks.
+    /// This is meant for operational recovery, e.g. to undo a range of bad blocks within the
+    /// current batch. It does nothing if the height is not below the current head.
+    pub fn rollback_to(&mut self, target_height: u32) -> Result<(), PushError> {
+        if target_height >= self.state.main_chain.head.block_number() {
+            return Ok(());
+        }
+
+        if target_height < self.state.macro_info.head.block_number() {
+            warn!(
+                target_height,
+                macro_head = %self.state.macro_info.head,
+                reason = "rollback would revert a macro block",
+                "Rejecting rollback"
+            );
+            return Err(PushError::InvalidFork);
+        }
+
+        // Revert infos of blocks deeper than the maximum rebranch depth may have been pruned,
+        // thus these blocks can't be reverted anymore.
+        let head_height = self.state.main_chain.head.block_number();
+        let max_depth = self
+            .config
+            .max_rebranch_depth
+            .filter(|_| self.config.prune_revert_infos);
+        if let Some(max_depth) = max_depth {
+            if head_height - target_height > max_depth {
+                warn!(
+                    target_height,
+                    head_height,
+                    max_rebranch_depth = max_depth,
+                    reason = "revert infos of the blocks to revert were pruned",
+                    "Rejecting rollback"
+                );
+                return Err(PushError::InvalidFork);
+            }
+        }
+
+        let target_hash = self
+            .get_block_hash_at(target_height)
+            .ok_or(BlockchainError::BlockNotFound)?;
+        let mut target_info = self.chain_store.get_chain_info(&target_hash, true, None)?;
+
+        let mut write_txn = self.write_transaction();
+        let (mut revert_chain, block_logs) =
+            match self.revert_main_chain_to(&target_hash, &mut write_txn) {
+                Ok(r) => r,
+                Err(e) => {
+                    write_txn.abort();
+                    return Err(e);
+                }
+            };
+
+        // Unset on_main_chain flag / main_chain_successor on the reverted blocks.
+        for reverted_block in revert_chain.iter_mut() {
+            reverted_block.1.on_main_chain = false;
+            reverted_block.1.main_chain_successor = None;
+
+            self.chain_store.put_chain_info(
+                &mut write_txn,
+                &reverted_block.0,
+                &reverted_block.1,
+                false,
+            );
+        }
+
+        // The target block is the new head.
+        target_info.main_chain_successor = None;
+        self.chain_store
+            .put_chain_info(&mut write_txn, &target_hash, &target_info, false);
+        self.chain_store.set_head(&mut write_txn, &target_hash);
+        write_txn.commit();
+
+        // The fee accumulator is reset after every macro block, which can only be the target
+        // block since macro blocks are never reverted.
+        let reverted_fees = match revert_chain.first() {
+            Some((_, old_head_info)) if target_info.head.is_macro() => old_head_info.cum_tx_fees,
+            Some((_, old_head_info)) => old_head_info.cum_tx_fees - target_info.cum_tx_fees,
+            None => Coin::ZERO,
+        };
+
+        self.state.main_chain = target_info;
+        self.state.head_hash = target_hash;
+
+        let reverted_blocks: Vec<_> = revert_chain
+            .into_iter()
+            .rev()
+            .map(|(hash, chain_info)| (hash, chain_info.head))
+            .collect();
+
+        debug!(
+            block = %self.state.main_chain.head,
+            num_reverted_blocks = reverted_blocks.len(),
+            "Rolled back",
+        );
+
+        // We do not log errors if there are no listeners.
+        self.notifier
+            .send(BlockchainEvent::Rebranched(reverted_blocks, vec![]))
+            .ok();
+        self.notifier
+            .send(BlockchainEvent::RebranchedWithFees(reverted_fees))
+            .ok();
+
+        send_vec(&self.log_notifier, block_logs);
+
+        Ok(())
+    }
+
+    /// Returns the hash of the first transaction of the given micro block that is already
+    /// included in the validity window of the main chain, if any.
+    fn find_duplicate_transaction(
+        &self,
+        block: &Block,
+        txn: &TransactionProxy,
+    ) -> Option<Blake2bHash> {
+        if !block.is_micro() {
+            return None;
+        }
+
+        block
+            .transactions()?
+            .iter()
+            .map(|transaction| transaction.get_raw_transaction().hash::<Blake2bHash>())
+            .find(|tx_hash| self.contains_tx_in_validity_window(tx_hash, Some(txn)))
     }
 
     pub(super) fn check_and_commit(
@@ -536,23 +889,14 @@ impl Blockchain {
         block_logger: &mut BlockLogger,
     ) -> Result<u64, PushError> {
         // Check transactions against replay attacks. This is only necessary for micro blocks.
-        if block.is_micro() {
-            let transactions = block.transactions();
-
-            if let Some(tx_vec) = transactions {
-                for transaction in tx_vec {
-                    let tx_hash = transaction.get_raw_transaction().hash();
-                    if self.contains_tx_in_validity_window(&tx_hash, Some(txn)) {
-                        warn!(
-                            %block,
-                            reason = "transaction already included",
-                            transaction_hash = %tx_hash,
-                            "Rejecting block",
-                        );
-                        return Err(PushError::DuplicateTransaction);
-                    }
-                }
-            }
+        if let Some(tx_hash) = self.find_duplicate_transaction(block, txn) {
+            warn!(
+                %block,
+                reason = "transaction already included",
+                transaction_hash = %tx_hash,
+                "Rejecting block",
+            );
+            return Err(PushError::DuplicateTransaction);
         }
 
         // Macro blocks: Verify the state against the block before modifying the staking contract.
@@ -646,4 +990,66 @@ impl Blockchain {
             }
         }
     }
+
+    /// Scans the stored micro blocks at the given block number for pairs of blocks produced in the
+    /// same slot, i.e. with the same VRF seed entropy, and returns a fork proof for each pair.
+    /// Like the fork detection when pushing blocks, skip blocks are not considered and the
+    /// justifications of the stored blocks are assumed to be valid.
+    pub fn find_fork_proofs_at(&self, block_number: u32) -> Vec<ForkProof> {
+        let txn = self.read_transaction();
+
+        let micro_blocks: Vec<MicroBlock> = self
+            .chain_store
+            .get_blocks_at(block_number, false, Some(&txn))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|block| block.is_micro() && !block.is_skip())
+            .map(|block| block.unwrap_micro())
+            .collect();
+
+        let mut proofs = vec![];
+        for (i, block1) in micro_blocks.iter().enumerate() {
+            for block2 in &micro_blocks[i + 1..] {
+                if block1.header.seed.entropy() != block2.header.seed.entropy() {
+                    continue;
+                }
+
+                // Both blocks were produced by the proposer of the slot determined by the seed of
+                // their (common) predecessor.
+                let validator_address = match self
+                    .chain_store
+                    .get_block(&block1.header.parent_hash, false, Some(&txn))
+                    .and_then(|parent| {
+                        self.get_proposer(
+                            block_number,
+                            block_number,
+                            parent.seed().entropy(),
+                            Some(&txn),
+                        )
+                    }) {
+                    Ok(slot) => slot.validator.address,
+                    Err(_) => continue,
+                };
+
+                // Blocks without a proposer signature can't be part of a fork proof.
+                let (
+                    Some(MicroJustification::Micro(justification1)),
+                    Some(MicroJustification::Micro(justification2)),
+                ) = (&block1.justification, &block2.justification)
+                else {
+                    continue;
+                };
+
+                proofs.push(ForkProof::new(
+                    validator_address,
+                    block1.header.clone(),
+                    justification1.clone(),
+                    block2.header.clone(),
+                    justification2.clone(),
+                ));
+            }
+        }
+
+        proofs
+    }
 }
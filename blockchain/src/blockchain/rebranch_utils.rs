@@ -1,7 +1,8 @@
 use std::error::Error;
 
 use nimiq_account::{BlockLog, BlockLogger};
-use nimiq_blockchain_interface::{ChainInfo, PushError};
+use nimiq_block::Block;
+use nimiq_blockchain_interface::{ChainAnalysis, ChainInfo, ChainOrdering, PushError};
 use nimiq_database::{TransactionProxy, WriteTransactionProxy};
 use nimiq_hash::Blake2bHash;
 use nimiq_primitives::trie::trie_diff::TrieDiff;
@@ -14,7 +15,7 @@ impl Blockchain {
     ///
     /// Returns the common ancestor as .0 and the chain leading to the common ancestor backwards from `block_hash` as .1
     /// The block with `block_hash` is included.
-    pub(super) fn find_common_ancestor(
+    pub(super) fn find_main_chain_ancestor(
         &self,
         block_hash: Blake2bHash,
         chain_info: ChainInfo,
@@ -77,6 +78,117 @@ impl Blockchain {
         Ok((current, fork_chain))
     }
 
+    /// Returns the hash and the height of the lowest common ancestor of the blocks `a` and `b`.
+    /// Both chains are walked back through the chain store until they meet.
+    /// Returns `None` if one of the blocks is unknown or if the chains don't converge in the
+    /// stored part of the chain, e.g. because older blocks were pruned.
+    pub fn find_common_ancestor(
+        &self,
+        a: &Blake2bHash,
+        b: &Blake2bHash,
+    ) -> Option<(Blake2bHash, u32)> {
+        let txn = self.read_transaction();
+
+        let get_head = |hash: &Blake2bHash| {
+            self.chain_store
+                .get_chain_info(hash, false, Some(&txn))
+                .map(|chain_info| chain_info.head)
+        };
+        let mut block_a = (a.clone(), get_head(a).ok()?);
+        let mut block_b = (b.clone(), get_head(b).ok()?);
+
+        while block_a.0 != block_b.0 {
+            // Move the higher block (or both if they are at the same height) back to its parent.
+            let height_a = block_a.1.block_number();
+            let height_b = block_b.1.block_number();
+
+            if height_a >= height_b {
+                let parent_hash = block_a.1.parent_hash().clone();
+                block_a = (parent_hash.clone(), get_head(&parent_hash).ok()?);
+            }
+            if height_b >= height_a {
+                let parent_hash = block_b.1.parent_hash().clone();
+                block_b = (parent_hash.clone(), get_head(&parent_hash).ok()?);
+            }
+        }
+
+        let height = block_a.1.block_number();
+        Some((block_a.0, height))
+    }
+
+    /// Returns the hash of the lowest common ancestor of the blocks `a` and `b`. Only the
+    /// reversible part of the chain, i.e. the blocks since the last macro block, is considered.
+    /// Returns `None` if one of the blocks is unknown or if the blocks diverge before the last
+    /// macro block.
+    pub fn common_ancestor(&self, a: &Blake2bHash, b: &Blake2bHash) -> Option<Blake2bHash> {
+        let macro_block_number = self.state.macro_info.head.block_number();
+
+        self.find_common_ancestor(a, b)
+            .filter(|(_, height)| *height >= macro_block_number)
+            .map(|(hash, _)| hash)
+    }
+
+    /// Returns the hashes of the blocks that would be adopted when rebranching to the fork ending
+    /// in `fork_tip`, in ascending order. Neither reverts nor account changes are computed.
+    /// The list is empty if `fork_tip` is already on the main chain.
+    /// Returns `None` if the fork tip (or one of its predecessors) is unknown.
+    pub fn fork_adopt_list(&self, fork_tip: &Blake2bHash) -> Option<Vec<Blake2bHash>> {
+        let txn = self.read_transaction();
+
+        let mut adopt_list = vec![];
+        let mut hash = fork_tip.clone();
+        let mut chain_info = self
+            .chain_store
+            .get_chain_info(&hash, false, Some(&txn))
+            .ok()?;
+
+        while !chain_info.on_main_chain {
+            let prev_hash = chain_info.head.parent_hash().clone();
+            adopt_list.push(hash);
+
+            chain_info = self
+                .chain_store
+                .get_chain_info(&prev_hash, false, Some(&txn))
+                .ok()?;
+            hash = prev_hash;
+        }
+
+        adopt_list.reverse();
+        Some(adopt_list)
+    }
+
+    /// Compares the chain ending in `block` to the main chain, the same way `push` does, and
+    /// returns the resulting ordering together with the fork height and the first skip blocks
+    /// found on either chain. The block itself does not need to be stored yet, but its parent
+    /// must be known; otherwise the ordering is `Unknown`.
+    pub fn analyze_chain_ordering(&self, block: &Block) -> ChainAnalysis {
+        let txn = self.read_transaction();
+
+        let prev_info =
+            match self
+                .chain_store
+                .get_chain_info(block.parent_hash(), false, Some(&txn))
+            {
+                Ok(prev_info) => prev_info,
+                Err(_) => {
+                    return ChainAnalysis {
+                        ordering: ChainOrdering::Unknown,
+                        fork_height: None,
+                        our_skip_block: None,
+                        their_skip_block: None,
+                    }
+                }
+            };
+
+        ChainOrdering::analyze_chains(
+            self,
+            block,
+            &prev_info,
+            |hash| self.get_chain_info(hash, false, Some(&txn)),
+            |height| self.get_block_at(height, false, Some(&txn)),
+        )
+    }
+
     /// Reverts all blocks until the common ancestor given as an argument is reached.
     /// After that applies all blocks given as target_chain in reverse order or until a block fails
     /// to be applied.
@@ -92,86 +204,11 @@ impl Blockchain {
         (Vec<(Blake2bHash, ChainInfo)>, Vec<BlockLog>),
         Vec<(Blake2bHash, ChainInfo, Option<TrieDiff>)>,
     > {
-        // Keeps track of the currently investigated block
-        let mut current = (self.state.head_hash.clone(), self.state.main_chain.clone());
-        // Collects the reverted blocks
-        let mut revert_chain: Vec<(Blake2bHash, ChainInfo)> = vec![];
-        // Keep track of block logs
-        let mut block_logs = vec![];
-
         // Start reverting blocks until the common ancestor is reached.
-        while current.0 != ancestor.0 {
-            let block = current.1.head.clone();
-
-            // Macro blocks cannot be reverted.
-            if block.is_macro() {
-                panic!("Trying to rebranch across macro block {block}");
-            }
-
-            // Retrieve the predecessor for later use.
-            let prev_hash = block.parent_hash().clone();
-            let prev_info = self
-                .chain_store
-                .get_chain_info(&prev_hash, true, Some(write_txn))
-                .expect("Corrupted store: Failed to find main chain predecessor while rebranching");
-
-            // If previously a part of the accounts tree was missing the corresponding chunk must be reverted as well.
-            if let Some(ref prev_missing_range) = current.1.prev_missing_range {
-                self.state
-                    .accounts
-                    .revert_chunk(&mut write_txn.into(), prev_missing_range.start.clone())
-                    .map_err(|error| {
-                        warn!(
-                            %block,
-                            chain_info = ?current.1,
-                            ?error,
-                            "Failed to revert chunk while rebranching",
-                        );
-                        // The revert failed, but there are no blocks to remove.
-                        vec![]
-                    })?;
-            }
-
-            // Keep track of the logs for the upcoming revert
-            let mut block_logger = BlockLogger::new_reverted(block.hash(), block.block_number());
-            // Revert the accounts
-            let total_tx_size = self
-                .revert_accounts(
-                    &self.state.accounts,
-                    &mut write_txn.into(),
-                    &block,
-                    &mut block_logger,
-                )
-                .map_err(|error| {
-                    warn!(
-                        %block,
-                        chain_info = ?current.1,
-                        ?error,
-                        "Failed to revert accounts while rebranching",
-                    );
-                    // The revert failed, but there are no blocks to remove.
-                    vec![]
-                })?;
-            // Push the collected revert logs into the block logs collection.
-            block_logs.push(block_logger.build(total_tx_size));
-
-            // Verify accounts hash if the tree is complete or changes only happened in the complete part.
-            if let Some(accounts_hash) = self.state.accounts.get_root_hash(Some(write_txn)) {
-                assert_eq!(
-                    prev_info.head.state_root(),
-                    &accounts_hash,
-                    "Inconsistent state after reverting block {} - {:?}",
-                    block,
-                    block,
-                );
-            }
-
-            // Block was reverted, add it to the reverted chain collection.
-            revert_chain.push(current);
-
-            // Continue with the predecessor.
-            current = (prev_hash, prev_info);
-        }
+        let (mut revert_chain, mut block_logs) = self
+            .revert_main_chain_to(&ancestor.0, write_txn)
+            // The revert failed, but there are no blocks to remove.
+            .map_err(|_| vec![])?;
         // Revert to common ancestor is done.
 
         // Next, push each block of the target chain.
@@ -257,4 +294,96 @@ impl Blockchain {
 
         Ok((revert_chain, block_logs))
     }
+
+    /// Reverts the main chain blocks, starting at the head, until the block with the given hash
+    /// is reached. The chain infos and the head in the chain store are not updated, this is up to
+    /// the caller.
+    ///
+    /// Returns the reverted chain, starting at the head, and the block logs of the reverts.
+    pub(super) fn revert_main_chain_to(
+        &self,
+        ancestor_hash: &Blake2bHash,
+        write_txn: &mut WriteTransactionProxy,
+    ) -> Result<(Vec<(Blake2bHash, ChainInfo)>, Vec<BlockLog>), PushError> {
+        // Keeps track of the currently investigated block
+        let mut current = (self.state.head_hash.clone(), self.state.main_chain.clone());
+        // Collects the reverted blocks
+        let mut revert_chain: Vec<(Blake2bHash, ChainInfo)> = vec![];
+        // Keep track of block logs
+        let mut block_logs = vec![];
+
+        // Start reverting blocks until the ancestor is reached.
+        while current.0 != *ancestor_hash {
+            let block = current.1.head.clone();
+
+            // Macro blocks cannot be reverted.
+            if block.is_macro() {
+                panic!("Trying to revert across macro block {block}");
+            }
+
+            // Retrieve the predecessor for later use.
+            let prev_hash = block.parent_hash().clone();
+            let prev_info = self
+                .chain_store
+                .get_chain_info(&prev_hash, true, Some(write_txn))
+                .expect("Corrupted store: Failed to find main chain predecessor while reverting");
+
+            // If previously a part of the accounts tree was missing the corresponding chunk must be reverted as well.
+            if let Some(ref prev_missing_range) = current.1.prev_missing_range {
+                self.state
+                    .accounts
+                    .revert_chunk(&mut write_txn.into(), prev_missing_range.start.clone())
+                    .map_err(|error| {
+                        warn!(
+                            %block,
+                            chain_info = ?current.1,
+                            ?error,
+                            "Failed to revert chunk",
+                        );
+                        PushError::AccountsError(error)
+                    })?;
+            }
+
+            // Keep track of the logs for the upcoming revert
+            let mut block_logger = BlockLogger::new_reverted(block.hash(), block.block_number());
+            // Revert the accounts
+            let total_tx_size = self
+                .revert_accounts(
+                    &self.state.accounts,
+                    &mut write_txn.into(),
+                    &block,
+                    &mut block_logger,
+                )
+                .map_err(|error| {
+                    warn!(
+                        %block,
+                        chain_info = ?current.1,
+                        ?error,
+                        "Failed to revert accounts",
+                    );
+                    error
+                })?;
+            // Push the collected revert logs into the block logs collection.
+            block_logs.push(block_logger.build(total_tx_size));
+
+            // Verify accounts hash if the tree is complete or changes only happened in the complete part.
+            if let Some(accounts_hash) = self.state.accounts.get_root_hash(Some(write_txn)) {
+                assert_eq!(
+                    prev_info.head.state_root(),
+                    &accounts_hash,
+                    "Inconsistent state after reverting block {} - {:?}",
+                    block,
+                    block,
+                );
+            }
+
+            // Block was reverted, add it to the reverted chain collection.
+            revert_chain.push(current);
+
+            // Continue with the predecessor.
+            current = (prev_hash, prev_info);
+        }
+
+        Ok((revert_chain, block_logs))
+    }
 }
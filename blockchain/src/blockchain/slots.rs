@@ -1,13 +1,63 @@
+use std::{
+    cmp,
+    collections::{HashSet, VecDeque},
+    sync::Arc,
+};
+
+use nimiq_account::{Account, DataStoreReadOps, StakingContract};
+use nimiq_block::Block;
 use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainError};
+use nimiq_collections::BitSet;
 use nimiq_database::TransactionProxy;
+use nimiq_hash::{Blake2sHash, Hash};
+use nimiq_keys::Address;
 use nimiq_primitives::{
     policy::Policy,
-    slots_allocation::{Slot, Validators},
+    slots_allocation::{JailedValidator, PenalizedSlot, Slot, Validators},
 };
+use nimiq_transaction::historic_transaction::{HistoricTransaction, HistoricTransactionData};
 use nimiq_vrf::{VrfEntropy, VrfSeed};
 
 use crate::Blockchain;
 
+/// The maximum number of past epochs whose validators are kept in the slots cache.
+const SLOTS_CACHE_CAPACITY: usize = 4;
+
+/// A least recently used cache of the validators of past epochs, most recently used first.
+pub(crate) type SlotsCache = VecDeque<(u32, Arc<Validators>)>;
+
+/// The JSON representation of a validator and its slots, as returned by
+/// `Blockchain::slots_as_json`.
+#[cfg(feature = "json")]
+#[derive(Clone, Debug, PartialEq, Eq, nimiq_serde::Serialize, nimiq_serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorSlotsJson {
+    /// The user friendly address of the validator.
+    pub address: String,
+    /// The hex encoded compressed BLS voting key.
+    pub voting_key: String,
+    /// The hex encoded Schnorr signing key.
+    pub signing_key: String,
+    /// The first slot number owned by the validator.
+    pub first_slot: u16,
+    /// The number of slots owned by the validator.
+    pub num_slots: u16,
+    /// The user friendly reward address of the validator, if it is still in the staking contract.
+    pub reward_address: Option<String>,
+}
+
+/// The punishments that were applied during a finalized epoch, as returned by
+/// `Blockchain::punishments_for_epoch`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochPunishments {
+    /// The slots that were penalized for skip blocks, in the order they were applied.
+    pub penalized_slots: Vec<PenalizedSlot>,
+    /// The validators that were jailed for equivocation proofs, in the order they were applied.
+    pub jailed_validators: Vec<JailedValidator>,
+    /// The slots of the next epoch that start out punished, as committed in the election block.
+    pub next_epoch_punished_set: BitSet,
+}
+
 /// Implements methods to handle slots and validators.
 impl Blockchain {
     /// Gets the active validators for a given epoch.
@@ -16,6 +66,17 @@ impl Blockchain {
         epoch: u32,
         txn: Option<&TransactionProxy>,
     ) -> Result<Validators, BlockchainError> {
+        self.get_shared_validators_for_epoch(epoch, txn)
+            .map(|validators| Validators::clone(&validators))
+    }
+
+    /// Gets the active validators for a given epoch, without copying them. This is cheaper than
+    /// `get_validators_for_epoch` for callers that only need to read the validators.
+    pub fn get_shared_validators_for_epoch(
+        &self,
+        epoch: u32,
+        txn: Option<&TransactionProxy>,
+    ) -> Result<Arc<Validators>, BlockchainError> {
         let current_epoch = Policy::epoch_at(self.state.main_chain.head.block_number());
 
         if epoch == current_epoch {
@@ -31,7 +92,12 @@ impl Blockchain {
         } else if epoch == 0 {
             Err(BlockchainError::InvalidEpoch)
         } else {
-            self.chain_store
+            if let Some(validators) = self.cached_validators(epoch) {
+                return Ok(validators);
+            }
+
+            let validators = self
+                .chain_store
                 .get_block_at(
                     Policy::election_block_of(epoch - 1).ok_or(BlockchainError::InvalidEpoch)?,
                     true,
@@ -39,16 +105,323 @@ impl Blockchain {
                 )?
                 .unwrap_macro()
                 .get_validators()
-                .ok_or(BlockchainError::NoValidatorsFound)
+                .ok_or(BlockchainError::NoValidatorsFound)?;
+
+            let validators = Arc::new(validators);
+            self.cache_validators(epoch, Arc::clone(&validators));
+            Ok(validators)
+        }
+    }
+
+    /// Returns the cached validators of the given epoch, if any, and marks them as most recently
+    /// used.
+    fn cached_validators(&self, epoch: u32) -> Option<Arc<Validators>> {
+        let mut cache = self.slots_cache.lock();
+        let index = cache
+            .iter()
+            .position(|(cached_epoch, _)| *cached_epoch == epoch)?;
+        let entry = cache.remove(index)?;
+        let validators = Arc::clone(&entry.1);
+        cache.push_front(entry);
+        Some(validators)
+    }
+
+    /// Adds the validators of the given epoch to the slots cache, evicting the least recently
+    /// used entry if the cache is full.
+    fn cache_validators(&self, epoch: u32, validators: Arc<Validators>) {
+        let mut cache = self.slots_cache.lock();
+        cache.retain(|(cached_epoch, _)| *cached_epoch != epoch);
+        cache.push_front((epoch, validators));
+        cache.truncate(SLOTS_CACHE_CAPACITY);
+    }
+
+    /// Clears the slots cache. This must be called whenever the election head changes, so that
+    /// no stale validators are returned afterwards.
+    pub(crate) fn invalidate_slots_cache(&self) {
+        self.slots_cache.lock().clear();
+    }
+
+    /// Computes the public key tree root of the current validators. This is the same root that
+    /// the election block which elected the current validators commits to in its body.
+    pub fn current_pk_tree_root(&self) -> Option<Blake2sHash> {
+        self.current_validators()
+            .map(|validators| validators.hash::<Blake2sHash>())
+    }
+
+    /// Rebuilds the cached current and previous slots from the election blocks in the chain store.
+    /// This mirrors the logic used when loading the blockchain and can be used to restore the
+    /// cached slots if they became inconsistent with the stored election chain.
+    pub fn resync_slots(&mut self) -> Result<(), BlockchainError> {
+        let election_head = self
+            .chain_store
+            .get_block_at(Policy::last_election_block(self.block_number()), true, None)?
+            .unwrap_macro();
+
+        if !election_head.is_election() {
+            return Err(BlockchainError::InconsistentState);
         }
+
+        let current_slots = election_head
+            .get_validators()
+            .ok_or(BlockchainError::NoValidatorsFound)?;
+
+        let prev_block =
+            self.chain_store
+                .get_block(&election_head.header.parent_election_hash, true, None);
+
+        let previous_slots = match prev_block {
+            Ok(Block::Macro(prev_election_block)) => prev_election_block.get_validators(),
+            Ok(Block::Micro(_)) => return Err(BlockchainError::InconsistentState),
+            // At genesis there is no previous election block, use the same default as `init`.
+            Err(_) if election_head.block_number() == Policy::genesis_block_number() => {
+                Some(Validators::default())
+            }
+            // The previous election block wasn't found.
+            Err(_) => None,
+        };
+
+        self.state.current_slots = Some(Arc::new(current_slots));
+        self.state.previous_slots = previous_slots.map(Arc::new);
+
+        Ok(())
     }
 
     /// Calculates the next validators from a given seed.
-    pub fn next_validators(&self, seed: &VrfSeed) -> Validators {
+    /// Returns `BlockchainError::MissingStakingContract` if the account at the staking contract
+    /// address is not available or is not a staking contract.
+    pub fn next_validators(&self, seed: &VrfSeed) -> Result<Validators, BlockchainError> {
+        let txn = self.read_transaction();
+        let staking_contract = match self
+            .state
+            .accounts
+            .get(&Policy::STAKING_CONTRACT_ADDRESS, Some(&txn))
+        {
+            Ok(Account::Staking(staking_contract)) => staking_contract,
+            _ => return Err(BlockchainError::MissingStakingContract),
+        };
+        let data_store = self.get_staking_contract_store();
+        Ok(Self::compute_next_validators(
+            &staking_contract,
+            &data_store.read(&txn),
+            seed,
+        ))
+    }
+
+    /// Calculates the next validators from the given staking contract, a reader of its data store
+    /// and seed. Unlike `next_validators`, this doesn't read from the current state, so the
+    /// validator selection can be computed for any state. The data store reader must belong to
+    /// the same state as the staking contract.
+    pub fn compute_next_validators<R: DataStoreReadOps>(
+        staking_contract: &StakingContract,
+        data_store: &R,
+        seed: &VrfSeed,
+    ) -> Validators {
+        staking_contract.select_validators(data_store, seed)
+    }
+
+    /// Returns the owners of all slots that are active at the given block number, ordered by
+    /// slot number. The slot assignment is fixed for a whole epoch.
+    /// Returns `None` if the validators for the block's epoch can't be determined.
+    pub fn get_slot_owners_at(
+        &self,
+        block_number: u32,
+        txn: Option<&TransactionProxy>,
+    ) -> Option<Vec<Slot>> {
+        let validators = self
+            .get_validators_for_epoch(Policy::epoch_at(block_number), txn)
+            .ok()?;
+
+        let slots = (0..Policy::SLOTS)
+            .map(|slot_number| Slot {
+                number: slot_number,
+                band: validators.get_band_from_slot(slot_number),
+                validator: validators.get_validator_by_slot_number(slot_number).clone(),
+            })
+            .collect();
+
+        Some(slots)
+    }
+
+    /// Returns the addresses of the validators whose slots are disabled during the given batch,
+    /// i.e. the owners of the slots in the punished set of the macro block preceding the batch.
+    /// Returns `None` if that macro block or the validators of the batch are unknown.
+    pub fn disabled_validators(&self, batch: u32) -> Option<Vec<Address>> {
+        let block_number = Policy::macro_block_of(batch.checked_sub(1)?)?;
+        let disabled_slots = self
+            .chain_store
+            .get_block_at(block_number, true, None)
+            .ok()?
+            .unwrap_macro()
+            .body?
+            .next_batch_initial_punished_set;
+
+        let validators = self
+            .get_validators_for_epoch(Policy::epoch_at(block_number + 1), None)
+            .ok()?;
+
+        // Slots are sorted, so the slots of a validator are adjacent.
+        let mut addresses: Vec<Address> = vec![];
+        for slot in disabled_slots.iter() {
+            let address = &validators.get_validator_by_slot_number(slot as u16).address;
+            if addresses.last() != Some(address) {
+                addresses.push(address.clone());
+            }
+        }
+
+        Some(addresses)
+    }
+
+    /// Returns the punishments that were applied during the given epoch, read from the penalize
+    /// and jail events in the epoch's history.
+    /// Returns `None` for the genesis epoch, if the epoch is not finalized yet, if the body of
+    /// its election block is not available or if its history is not in the history store.
+    pub fn punishments_for_epoch(&self, epoch: u32) -> Option<EpochPunishments> {
+        if epoch == 0 {
+            return None;
+        }
+
+        let election_block_number = Policy::election_block_of(epoch)?;
+        if election_block_number > self.election_head().block_number() {
+            return None;
+        }
+
+        // The light history store only keeps the validity window, not the history of full epochs.
+        if self.config.light_history_store {
+            return None;
+        }
+
+        let txn = self.read_transaction();
+        let (first_block, last_block) = self.history_store.history_store_range(Some(&txn));
+        if epoch < Policy::epoch_at(first_block) || epoch > Policy::epoch_at(last_block) {
+            return None;
+        }
+
+        let next_epoch_punished_set = self
+            .chain_store
+            .get_block_at(election_block_number, true, Some(&txn))
+            .ok()?
+            .unwrap_macro()
+            .body?
+            .next_batch_initial_punished_set;
+
+        let mut punishments = EpochPunishments {
+            next_epoch_punished_set,
+            ..Default::default()
+        };
+        let mut f = |hist_tx: &HistoricTransaction| match &hist_tx.data {
+            HistoricTransactionData::Penalize(event) => {
+                punishments.penalized_slots.push(PenalizedSlot {
+                    slot: event.slot,
+                    validator_address: event.validator_address.clone(),
+                    offense_event_block: event.offense_event_block,
+                })
+            }
+            HistoricTransactionData::Jail(event) => {
+                punishments.jailed_validators.push(JailedValidator {
+                    slots: event.slots.clone(),
+                    validator_address: event.validator_address.clone(),
+                    offense_event_block: event.offense_event_block,
+                })
+            }
+            _ => {}
+        };
+        self.history_store
+            .for_each_epoch_transaction(epoch, Some(&txn), &mut f);
+
+        Some(punishments)
+    }
+
+    /// Returns the reward address and the number of slots of each current validator, in slot
+    /// order. Validators that can no longer be found in the staking contract are skipped.
+    pub fn current_reward_weights(&self) -> Vec<(Address, u16)> {
+        let Some(validators) = self.state.current_slots.as_ref() else {
+            return vec![];
+        };
+
         let staking_contract = self.get_staking_contract();
         let data_store = self.get_staking_contract_store();
         let txn = self.read_transaction();
-        staking_contract.select_validators(&data_store.read(&txn), seed)
+
+        validators
+            .iter()
+            .filter_map(|validator_slot| {
+                let validator = staking_contract
+                    .get_validator(&data_store.read(&txn), &validator_slot.address)?;
+                Some((validator.reward_address, validator_slot.num_slots()))
+            })
+            .collect()
+    }
+
+    /// Serializes the current validators to a JSON array of `ValidatorSlotsJson`, in slot order.
+    /// Returns an empty array if the current validators are unknown.
+    #[cfg(feature = "json")]
+    pub fn slots_as_json(&self) -> String {
+        let Some(validators) = self.state.current_slots.as_ref() else {
+            return "[]".to_string();
+        };
+
+        let staking_contract = self.get_staking_contract();
+        let data_store = self.get_staking_contract_store();
+        let txn = self.read_transaction();
+
+        let slots: Vec<ValidatorSlotsJson> = validators
+            .iter()
+            .map(|validator_slot| ValidatorSlotsJson {
+                address: validator_slot.address.to_user_friendly_address(),
+                voting_key: validator_slot.voting_key.compressed().to_hex(),
+                signing_key: validator_slot.signing_key.to_hex(),
+                first_slot: validator_slot.slots.start,
+                num_slots: validator_slot.num_slots(),
+                reward_address: staking_contract
+                    .get_validator(&data_store.read(&txn), &validator_slot.address)
+                    .map(|validator| validator.reward_address.to_user_friendly_address()),
+            })
+            .collect();
+
+        serde_json::to_string(&slots).expect("Failed to serialize slots")
+    }
+
+    /// Returns the numbers of the slots that proposed at least one block of the given epoch, as
+    /// opposed to the slots that were scheduled. Skip blocks are not attributed to any slot, so the
+    /// slots that were skipped are absent unless they proposed another block. For the current
+    /// epoch, only the blocks up to the head are considered.
+    /// Returns `None` if the epoch has not started yet or any of its blocks is unknown.
+    pub fn active_producers_in_epoch(&self, epoch: u32) -> Option<HashSet<u16>> {
+        let first_block = Policy::first_block_of(epoch)?;
+        let last_block = cmp::min(Policy::election_block_of(epoch)?, self.block_number());
+        if first_block > last_block {
+            return None;
+        }
+
+        let txn = self.read_transaction();
+        let mut parent = self
+            .chain_store
+            .get_block_at(first_block - 1, false, Some(&txn))
+            .ok()?;
+
+        let mut active_slots = HashSet::new();
+        for block_number in first_block..=last_block {
+            let block = self
+                .chain_store
+                .get_block_at(block_number, false, Some(&txn))
+                .ok()?;
+
+            if !block.is_skip() {
+                let slot = self
+                    .get_proposer(
+                        block_number,
+                        block.vrf_offset(),
+                        parent.seed().entropy(),
+                        Some(&txn),
+                    )
+                    .ok()?;
+                active_slots.insert(slot.number);
+            }
+
+            parent = block;
+        }
+
+        Some(active_slots)
     }
 
     pub fn get_proposer(
@@ -79,7 +452,7 @@ impl Blockchain {
 
         // Fetch the validators that are active in given block's epoch.
         let epoch_number = Policy::epoch_at(block_number);
-        let validators = self.get_validators_for_epoch(epoch_number, txn)?;
+        let validators = self.get_shared_validators_for_epoch(epoch_number, txn)?;
 
         // Get the validator that owns the proposer slot.
         let validator = validators.get_validator_by_slot_number(slot_number);
@@ -1,5 +1,5 @@
 use nimiq_account::BlockLogger;
-use nimiq_block::{Block, BlockError, MacroBlock, MacroBody};
+use nimiq_block::{Block, BlockError, EquivocationProof, ForkProof, MacroBlock, MacroBody};
 use nimiq_blockchain_interface::{AbstractBlockchain, ChainInfo, PushError};
 use nimiq_database::{
     traits::{ReadTransaction, WriteTransaction},
@@ -12,6 +12,14 @@ use crate::{BlockProducer, Blockchain};
 
 /// Implements methods to verify the validity of blocks.
 impl Blockchain {
+    /// Verifies whether the given block would be accepted on top of its predecessor, performing
+    /// the same checks as the push path before any state is modified. This never opens a write
+    /// transaction, so it can be used to evaluate a block without committing it.
+    pub fn dry_run_verify_block(&self, block: &Block) -> Result<(), PushError> {
+        let txn = self.read_transaction();
+        self.verify_block(&txn, block, false)
+    }
+
     /// Verifies a block for the current blockchain state.
     /// This method does a full verification on the block except for the block state checks.
     /// See `verify_block_state` for these type of checks.
@@ -21,6 +29,45 @@ impl Blockchain {
         block: &Block,
         trusted: bool,
     ) -> Result<(), PushError> {
+        self.verify_block_intrinsics(block)?;
+
+        // Check that the block is not too far in the future, if configured.
+        if let Some(max_timestamp_drift) = self.config.max_timestamp_drift {
+            let max_timestamp = self.time.now() + max_timestamp_drift;
+            if block.timestamp() > max_timestamp {
+                warn!(
+                    %block,
+                    timestamp = block.timestamp(),
+                    max_timestamp,
+                    reason = "Block timestamp too far in the future",
+                    "Rejecting block"
+                );
+                return Err(PushError::InvalidBlock(BlockError::InvalidTimestamp));
+            }
+        }
+
+        let predecessor = self.verify_block_successor(txn, block)?;
+
+        // In trusted don't do slot related checks since they are mostly signature verifications
+        // that can be slow.
+        if !trusted {
+            self.verify_block_slots(txn, block, &predecessor)?;
+
+            // Verify that the transactions in the block are valid.
+            self.verify_transactions(block)?;
+
+            // Verify that the equivocation proofs are valid.
+            self.verify_equivocation_proofs(block, txn)?;
+        }
+
+        // Verify that the transactions in the block pass the transaction filter, if any.
+        self.verify_transaction_filter(block)?;
+
+        Ok(())
+    }
+
+    /// Performs the checks of a block that don't depend on the blockchain state.
+    fn verify_block_intrinsics(&self, block: &Block) -> Result<(), PushError> {
         // We expect full blocks (with body) here.
         block
             .body()
@@ -29,6 +76,16 @@ impl Blockchain {
         // Perform block intrinsic checks.
         block.verify(self.network_id)?;
 
+        Ok(())
+    }
+
+    /// Verifies that the block is a valid successor of its predecessor and, for macro blocks, of
+    /// the current election block. Returns the predecessor.
+    fn verify_block_successor(
+        &self,
+        txn: &DBTransaction,
+        block: &Block,
+    ) -> Result<Block, PushError> {
         // Fetch predecessor block. Fail if it doesn't exist.
         let predecessor = self
             .get_chain_info(block.parent_hash(), false, Some(txn))
@@ -66,36 +123,39 @@ impl Blockchain {
             }
         }
 
-        // In trusted don't do slot related checks since they are mostly signature verifications
-        // that can be slow.
-        if !trusted {
-            // Get the proposer for this block. The block's predecessor is not necessarily on the
-            // main chain, thus the predecessor's VRF seed is used.
-            let proposer = self
-                .get_proposer(
-                    block.block_number(),
-                    block.vrf_offset(),
-                    predecessor.seed().entropy(),
-                    Some(txn),
-                )
-                .map_err(|error| {
-                    warn!(%error, %block, reason = "Failed to determine block proposer", "Rejecting block");
-                    PushError::Orphan
-                })?
-                .validator;
-
-            // Verify that the block is valid for the given proposer.
-            block.verify_proposer(&proposer.signing_key, predecessor.seed())?;
+        Ok(predecessor)
+    }
 
-            // Verify that the block is valid for the current validators.
-            block.verify_validators(&self.current_validators().unwrap())?;
+    /// Verifies the block against its proposer and the validators of its epoch.
+    fn verify_block_slots(
+        &self,
+        txn: &DBTransaction,
+        block: &Block,
+        predecessor: &Block,
+    ) -> Result<(), PushError> {
+        // Get the proposer for this block. The block's predecessor is not necessarily on the
+        // main chain, thus the predecessor's VRF seed is used.
+        let proposer = self
+            .get_proposer(
+                block.block_number(),
+                block.vrf_offset(),
+                predecessor.seed().entropy(),
+                Some(txn),
+            )
+            .map_err(|error| {
+                warn!(%error, %block, reason = "Failed to determine block proposer", "Rejecting block");
+                PushError::Orphan
+            })?
+            .validator;
 
-            // Verify that the transactions in the block are valid.
-            self.verify_transactions(block)?;
+        // Verify that the block is valid for the given proposer.
+        block.verify_proposer(&proposer.signing_key, predecessor.seed())?;
 
-            // Verify that the equivocation proofs are valid.
-            self.verify_equivocation_proofs(block, txn)?;
-        }
+        // Verify that the block is valid for the validators of its epoch. These differ from
+        // the current validators when verifying a block of a past epoch.
+        let validators = self
+            .get_shared_validators_for_epoch(Policy::epoch_at(block.block_number()), Some(txn))?;
+        block.verify_validators(&validators)?;
 
         Ok(())
     }
@@ -113,6 +173,25 @@ impl Blockchain {
         Ok(())
     }
 
+    /// Rejects the block if the transaction filter rejects any of its transactions. This applies
+    /// to trusted blocks as well.
+    fn verify_transaction_filter(&self, block: &Block) -> Result<(), BlockError> {
+        let transaction_filter = self.transaction_filter.read();
+        let Some(filter) = transaction_filter.as_ref() else {
+            return Ok(());
+        };
+
+        if let Some(transactions) = block.transactions() {
+            for transaction in transactions {
+                if !filter(transaction.get_raw_transaction()) {
+                    return Err(BlockError::FilteredTransaction);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Verifies a block against the blockchain state AFTER it gets updated with the block (ex: checking if
     /// an account has enough funds).
     /// It receives a block as input but that block is only required to have a header (the body and
@@ -213,6 +292,11 @@ impl Blockchain {
             .expect("Block body must be present");
 
         for equivocation_proof in &body.equivocation_proofs {
+            // Reject malformed fork proofs before doing any lookups or cryptographic checks.
+            if let EquivocationProof::Fork(fork_proof) = equivocation_proof {
+                self.validate_fork_proof_structure(fork_proof)?;
+            }
+
             if self
                 .history_store
                 .has_equivocation_proof(equivocation_proof.locator(), Some(txn))
@@ -221,17 +305,61 @@ impl Blockchain {
                     equivocation_proof.locator(),
                 ));
             }
-            let validators = self
-                .get_validators_for_epoch(
-                    Policy::epoch_at(equivocation_proof.block_number()),
-                    Some(txn),
-                )
-                .expect("Couldn't calculate validators");
-            equivocation_proof.verify(block.network(), &validators)?;
+            self.verify_equivocation_proof(block, equivocation_proof, txn)?;
         }
         Ok(())
     }
 
+    /// Verifies an equivocation proof of the given block against the validators of the epoch the
+    /// offense happened in.
+    fn verify_equivocation_proof(
+        &self,
+        block: &Block,
+        equivocation_proof: &EquivocationProof,
+        txn: &DBTransaction,
+    ) -> Result<(), PushError> {
+        let validators = self.get_shared_validators_for_epoch(
+            Policy::epoch_at(equivocation_proof.block_number()),
+            Some(txn),
+        )?;
+        equivocation_proof.verify(block.network(), &validators)?;
+        Ok(())
+    }
+
+    /// Verifies a block that is already stored again, without committing it. The block is checked
+    /// intrinsically, as a successor of its predecessor, against its proposer and the validators
+    /// of its epoch (including its justification), and its equivocation proofs are verified.
+    /// Checks that only hold before the block was applied, like duplicate transactions in the
+    /// validity window or already included equivocation proofs, are skipped.
+    pub fn verify_known_block(&self, txn: &DBTransaction, block: &Block) -> Result<(), PushError> {
+        self.verify_block_intrinsics(block)?;
+        let predecessor = self.verify_block_successor(txn, block)?;
+        self.verify_block_slots(txn, block, &predecessor)?;
+
+        if let Block::Micro(micro_block) = block {
+            let body = micro_block
+                .body
+                .as_ref()
+                .expect("Block body must be present");
+
+            for equivocation_proof in &body.equivocation_proofs {
+                if let EquivocationProof::Fork(fork_proof) = equivocation_proof {
+                    self.validate_fork_proof_structure(fork_proof)?;
+                }
+                self.verify_equivocation_proof(block, equivocation_proof, txn)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that a fork proof consists of two distinct headers for the same slot, i.e. with the
+    /// same block number and the same VRF entropy. No signatures are verified.
+    pub fn validate_fork_proof_structure(&self, proof: &ForkProof) -> Result<(), PushError> {
+        proof.verify_structure()?;
+        Ok(())
+    }
+
     /// Verifies a block against the blockchain state BEFORE changes to the accounts tree and thus to the staking contract.
     /// Some fields in the staking contract are cleared using the FinalizeBatch and FinalizeEpoch Inherents in preparation for the next batch.
     /// Thus, we need to compare the respective fields in the block before clearing the staking contract.
@@ -246,6 +374,11 @@ impl Blockchain {
             _ => return Ok(()),
         };
 
+        // When syncing from a trusted source, the macro body is not recomputed.
+        if self.config.trust_macro_bodies {
+            return Ok(());
+        }
+
         // If we don't have the staking contract, there is nothing we can check.
         let staking_contract = match self.get_staking_contract_if_complete(Some(txn)) {
             Some(staking_contract) => staking_contract,
@@ -259,7 +392,7 @@ impl Blockchain {
 
         // Verify validators.
         let validators = match macro_block.is_election() {
-            true => Some(self.next_validators(&macro_block.header.seed)),
+            true => Some(self.next_validators(&macro_block.header.seed)?),
             false => None,
         };
         if body.validators != validators {
@@ -487,7 +620,7 @@ impl Blockchain {
         let read_txn = self.read_transaction();
         // First the common ancestor of the two chains needs to be found.
         let (mut ancestor, mut fork_chain) =
-            self.find_common_ancestor(block.hash(), chain_info, None, &read_txn)?;
+            self.find_main_chain_ancestor(block.hash(), chain_info, None, &read_txn)?;
 
         read_txn.close();
 
@@ -1,21 +1,61 @@
-use std::ops::RangeFrom;
 #[cfg(feature = "metrics")]
 use std::sync::Arc;
+use std::{collections::HashSet, iter, mem::size_of, ops::RangeFrom};
 
-use nimiq_account::{Account, BlockState, DataStore, ReservedBalance, StakingContract};
-use nimiq_block::Block;
-use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainError, ChainInfo, Direction};
+use futures::{stream, stream::BoxStream, StreamExt};
+use nimiq_account::{
+    Account, BlockState, DataStore, DataStoreReadOps, ReservedBalance, StakingContract,
+};
+use nimiq_block::{Block, BlockInclusionProof, MacroBlock};
+use nimiq_blockchain_interface::{
+    AbstractBlockchain, BlockchainError, BlockchainEvent, ChainInfo, Direction,
+};
+use nimiq_bls::PublicKey as BlsPublicKey;
+use nimiq_collections::BitSet;
 use nimiq_database::{traits::WriteTransaction, TransactionProxy as DBTransaction};
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_primitives::{
-    account::AccountError, key_nibbles::KeyNibbles, policy::Policy, slots_allocation::Slot,
+    account::AccountError,
+    coin::Coin,
+    key_nibbles::KeyNibbles,
+    policy::Policy,
+    slots_allocation::{Slot, Validator, Validators},
+};
+use nimiq_serde::Serialize;
+use nimiq_transaction::{
+    historic_transaction::HistoricTransaction, history_proof::HistoryTreeProof, Transaction,
 };
-use nimiq_transaction::Transaction;
 
 #[cfg(feature = "metrics")]
 use crate::chain_metrics::BlockchainMetrics;
-use crate::{blockchain_state::BlockchainState, Blockchain};
+use crate::{
+    blockchain_state::{BlockchainState, HeadInfo},
+    reward::GenesisParameters,
+    Blockchain,
+};
+
+/// A serializable snapshot of the chain information of a block, as returned by
+/// `Blockchain::get_chain_info_export`.
+#[derive(Clone, Debug, PartialEq, Eq, nimiq_serde::Serialize, nimiq_serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfoExport {
+    /// The number of the block.
+    pub block_number: u32,
+    /// The batch the block belongs to.
+    pub batch_number: u32,
+    /// The epoch the block belongs to.
+    pub epoch_number: u32,
+    /// Whether the block is on the main chain.
+    pub on_main_chain: bool,
+    /// The sum of all transaction fees in the batch up to and including the block.
+    pub cum_tx_fees: Coin,
+    /// The slots that were disabled when the block was produced, i.e. the punished set of the
+    /// preceding macro block. Empty for the genesis block.
+    pub disabled_slots: Vec<u16>,
+    /// For macro blocks, the slots that start out punished in the next batch.
+    pub next_batch_punished_slots: Option<Vec<u16>>,
+}
 
 /// Implements several wrapper functions.
 impl Blockchain {
@@ -24,6 +64,101 @@ impl Blockchain {
         &self.state
     }
 
+    /// Returns an estimate of the memory (in bytes) used by the cached blockchain state: the chain
+    /// infos of the head and the last macro block, the election head, the current and previous
+    /// slots and the slots cache. Blocks are accounted with their serialized size and heap
+    /// allocations of the validators are approximated, so this is only a rough estimate.
+    pub fn state_memory_estimate(&self) -> usize {
+        fn chain_info_size(chain_info: &ChainInfo) -> usize {
+            size_of::<ChainInfo>() + chain_info.head.serialized_size()
+        }
+
+        fn validators_size(validators: &Validators) -> usize {
+            size_of::<Validators>()
+                + validators.num_validators()
+                    * (size_of::<Validator>() + size_of::<(Address, u16)>())
+        }
+
+        let state = self.state();
+        let slots_size: usize = state
+            .current_slots
+            .iter()
+            .chain(state.previous_slots.iter())
+            .map(|validators| validators_size(validators))
+            .sum();
+        let slots_cache_size: usize = self
+            .slots_cache
+            .lock()
+            .iter()
+            .map(|(_, validators)| size_of::<u32>() + validators_size(validators))
+            .sum();
+
+        chain_info_size(&state.main_chain)
+            + chain_info_size(&state.macro_info)
+            + state.election_head.serialized_size()
+            + slots_size
+            + slots_cache_size
+    }
+
+    /// Estimates the timestamp (in milliseconds) at which the next macro block will be produced.
+    /// The average block time over the blocks of the current batch is projected onto the number of
+    /// blocks remaining until the next macro block. If the current batch has no blocks yet, the
+    /// block separation time of the policy is assumed instead.
+    pub fn estimated_next_macro_timestamp(&self) -> u64 {
+        let head = &self.state.main_chain.head;
+        let macro_head = &self.state.macro_info.head;
+
+        let blocks_in_batch = u64::from(head.block_number() - macro_head.block_number());
+        let block_time = if blocks_in_batch > 0 {
+            head.timestamp().saturating_sub(macro_head.timestamp()) / blocks_in_batch
+        } else {
+            Policy::BLOCK_SEPARATION_TIME
+        };
+
+        let remaining_blocks =
+            u64::from(Policy::macro_block_after(head.block_number()) - head.block_number());
+
+        head.timestamp() + remaining_blocks * block_time
+    }
+
+    /// Returns the genesis supply, timestamp, hash and network ID of this blockchain.
+    pub fn genesis_parameters(&self) -> GenesisParameters {
+        GenesisParameters {
+            supply: self.genesis_supply,
+            timestamp: self.genesis_timestamp,
+            hash: self.genesis_hash.clone(),
+            network_id: self.network_id,
+        }
+    }
+
+    /// Returns the hashes of the head and of the election head, which are the parent hash and the
+    /// parent election hash of the next block. Both are read from the same state, so they are
+    /// consistent with each other.
+    pub fn next_block_parents(&self) -> (Blake2bHash, Blake2bHash) {
+        let state = self.state();
+        (state.head_hash.clone(), state.election_head_hash.clone())
+    }
+
+    /// Returns the hash, block number, skip block flag and timestamp of the head together with
+    /// the hashes of the macro and election heads. All fields are read from the same state, so
+    /// they are consistent with each other.
+    pub fn head_info(&self) -> HeadInfo {
+        let state = self.state();
+        HeadInfo {
+            hash: state.head_hash.clone(),
+            block_number: state.main_chain.head.block_number(),
+            is_skip: state.main_chain.head.is_skip(),
+            timestamp: state.main_chain.head.timestamp(),
+            macro_head_hash: state.macro_head_hash.clone(),
+            election_head_hash: state.election_head_hash.clone(),
+        }
+    }
+
+    /// Returns the hash of the head as persisted in the chain store.
+    pub fn head_hash_from_store(&self, txn: &DBTransaction) -> Option<Blake2bHash> {
+        self.chain_store.get_head(Some(txn))
+    }
+
     pub fn get_block_at(
         &self,
         height: u32,
@@ -34,6 +169,22 @@ impl Blockchain {
             .get_block_at(height, include_body, txn_option)
     }
 
+    /// Returns the hash of the main chain block at the given height without loading the block.
+    /// Returns `None` if the height is above the current head or the block is not available.
+    pub fn get_block_hash_at(&self, height: u32) -> Option<Blake2bHash> {
+        if height > self.block_number() {
+            return None;
+        }
+        self.chain_store.get_block_hash_at(height, None)
+    }
+
+    /// Returns true if there is a main chain block at the given height. Only the height index is
+    /// consulted, the block itself is not loaded.
+    /// Returns false for heights above the current head and for heights whose blocks were pruned.
+    pub fn contains_block_at(&self, height: u32) -> bool {
+        self.get_block_hash_at(height).is_some()
+    }
+
     pub fn get_block(
         &self,
         hash: &Blake2bHash,
@@ -55,6 +206,214 @@ impl Blockchain {
             .get_blocks(start_block_hash, count, include_body, direction, txn_option)
     }
 
+    /// Returns an iterator over the blocks following (`Direction::Forward`) or preceding
+    /// (`Direction::Backward`) the block with the given hash, excluding that block. Blocks are
+    /// fetched from the chain store on demand within a single read transaction. The iterator
+    /// stops at the first block that is not available in the store.
+    pub fn blocks_iter(
+        &self,
+        start_block_hash: &Blake2bHash,
+        include_body: bool,
+        direction: Direction,
+    ) -> impl Iterator<Item = Block> + '_ {
+        let txn = self.read_transaction();
+
+        let mut next_hash = match direction {
+            Direction::Forward => self
+                .chain_store
+                .get_chain_info(start_block_hash, false, Some(&txn))
+                .ok()
+                .and_then(|chain_info| chain_info.main_chain_successor),
+            Direction::Backward => self
+                .chain_store
+                .get_block(start_block_hash, false, Some(&txn))
+                .ok()
+                .map(|block| block.parent_hash().clone()),
+        };
+
+        iter::from_fn(move || {
+            let hash = next_hash.take()?;
+            match direction {
+                Direction::Forward => {
+                    let chain_info = self
+                        .chain_store
+                        .get_chain_info(&hash, include_body, Some(&txn))
+                        .ok()?;
+                    next_hash = chain_info.main_chain_successor;
+                    Some(chain_info.head)
+                }
+                Direction::Backward => {
+                    let block = self
+                        .chain_store
+                        .get_block(&hash, include_body, Some(&txn))
+                        .ok()?;
+                    next_hash = Some(block.parent_hash().clone());
+                    Some(block)
+                }
+            }
+        })
+    }
+
+    /// Returns a stream of blockchain events that first replays the events of the main chain blocks
+    /// following the block `since` and then continues with the live events. For every replayed
+    /// block an `Extended` event is emitted, followed by `Finalized` or `EpochFinalized` for macro
+    /// blocks, in the same order as when pushing them.
+    /// If `since` is unknown, not on the main chain or more than `max_replayed_blocks` blocks
+    /// behind the head, a single `Rebranched` event adopting the current head is emitted instead,
+    /// signalling that the listener needs to resync.
+    /// Events of pushes that are concurrently being finished may be delivered twice.
+    pub fn notifier_as_stream_from(
+        &self,
+        since: &Blake2bHash,
+    ) -> BoxStream<'static, BlockchainEvent> {
+        // Subscribe before replaying, so that no events are missed in between.
+        let live_events = self.notifier_as_stream();
+
+        let txn = self.read_transaction();
+        let replay_depth = self
+            .chain_store
+            .get_chain_info(since, false, Some(&txn))
+            .ok()
+            .filter(|chain_info| chain_info.on_main_chain)
+            .map(|chain_info| self.block_number() - chain_info.head.block_number())
+            .filter(|&depth| depth <= self.config.max_replayed_blocks);
+        let replayed_blocks = replay_depth.and_then(|depth| {
+            self.chain_store
+                .get_blocks(since, depth, false, Direction::Forward, Some(&txn))
+                .ok()
+        });
+
+        let mut replayed_events = vec![];
+        if let Some(blocks) = replayed_blocks {
+            for block in blocks {
+                let hash = block.hash();
+                replayed_events.push(BlockchainEvent::Extended(hash.clone()));
+                if block.is_election() {
+                    replayed_events.push(BlockchainEvent::EpochFinalized(hash));
+                } else if block.is_macro() {
+                    replayed_events.push(BlockchainEvent::Finalized(hash));
+                }
+            }
+        } else {
+            replayed_events.push(BlockchainEvent::Rebranched(
+                vec![],
+                vec![(self.head_hash(), self.head())],
+            ));
+        }
+
+        stream::iter(replayed_events).chain(live_events).boxed()
+    }
+
+    /// Returns the last `count` blocks of the main chain in descending order, starting with the
+    /// head. Fewer blocks are returned if the chain store does not contain enough blocks.
+    pub fn recent_blocks(&self, count: u32, include_body: bool) -> Vec<Block> {
+        if count == 0 {
+            return vec![];
+        }
+
+        let txn = self.read_transaction();
+        let head_hash = self.head_hash();
+        let Ok(head) = self.get_block(&head_hash, include_body, Some(&txn)) else {
+            return vec![];
+        };
+
+        let mut blocks = vec![head];
+        if let Ok(preceding) = self.get_blocks(
+            &head_hash,
+            count - 1,
+            include_body,
+            Direction::Backward,
+            Some(&txn),
+        ) {
+            blocks.extend(preceding);
+        }
+        blocks
+    }
+
+    /// Returns the main chain blocks with block numbers from `from` to `to` (both inclusive) in
+    /// ascending order. `to` is clamped to the current head. Returns an empty vector if
+    /// `from > to` or if the block at `from` is not available.
+    pub fn get_blocks_in_height_range(&self, from: u32, to: u32, include_body: bool) -> Vec<Block> {
+        let to = to.min(self.block_number());
+        if from > to {
+            return vec![];
+        }
+
+        let txn = self.read_transaction();
+        let Ok(first) = self
+            .chain_store
+            .get_block_at(from, include_body, Some(&txn))
+        else {
+            return vec![];
+        };
+
+        let following = self
+            .get_blocks(
+                &first.hash(),
+                to - from,
+                include_body,
+                Direction::Forward,
+                Some(&txn),
+            )
+            .unwrap_or_default();
+
+        let mut blocks = vec![first];
+        blocks.extend(following);
+        blocks
+    }
+
+    /// Removes the stored bodies of the main chain micro blocks below the given block number while
+    /// keeping their headers for chain navigation. Only blocks before the current election head
+    /// are pruned, since those belong to past epochs and can never be reverted. The current epoch
+    /// is never touched. Returns the number of pruned bodies.
+    ///
+    /// The height up to which bodies were pruned is stored, so that later calls only visit the
+    /// heights that were not pruned yet.
+    ///
+    /// For pruned blocks, `get_block_at` and `get_chain_info` return the header-only block even if
+    /// the body is requested, while `get_block` and `get_blocks` return
+    /// `BlockchainError::BlockNotFound` if the body is requested. Since block requests of peers
+    /// are served through `get_blocks`, a node that pruned bodies can't serve them anymore.
+    pub fn prune_bodies_before(&self, block_number: u32) -> usize {
+        let limit = block_number.min(self.state.election_head.block_number());
+
+        let mut txn = self.write_transaction();
+        let start = self
+            .chain_store
+            .get_pruned_bodies_height(Some(&txn))
+            .unwrap_or(self.genesis_block_number + 1)
+            .max(self.genesis_block_number + 1);
+        if start >= limit {
+            txn.abort();
+            return 0;
+        }
+
+        let mut pruned = 0;
+        for height in start..limit {
+            if Policy::is_macro_block_at(height) {
+                continue;
+            }
+
+            let Ok(chain_info) = self
+                .chain_store
+                .get_chain_info_at(height, false, Some(&txn))
+            else {
+                continue;
+            };
+
+            if self
+                .chain_store
+                .remove_block_body(&mut txn, &chain_info.head.hash())
+            {
+                pruned += 1;
+            }
+        }
+        self.chain_store.set_pruned_bodies_height(&mut txn, limit);
+        txn.commit();
+
+        pruned
+    }
+
     pub fn get_chain_info(
         &self,
         hash: &Blake2bHash,
@@ -65,6 +424,185 @@ impl Blockchain {
             .get_chain_info(hash, include_body, txn_option)
     }
 
+    /// Returns `true` if the block with the given hash is known and on the main chain.
+    pub fn is_on_main_chain(&self, hash: &Blake2bHash) -> bool {
+        matches!(
+            self.chain_store.get_chain_info(hash, false, None),
+            Ok(chain_info) if chain_info.on_main_chain
+        )
+    }
+
+    /// Returns a serializable snapshot of the chain information of the block with the given hash,
+    /// including the punished slots that apply to it. Returns `None` if the block is unknown.
+    pub fn get_chain_info_export(&self, hash: &Blake2bHash) -> Option<ChainInfoExport> {
+        fn to_slots(set: &BitSet) -> Vec<u16> {
+            set.iter().map(|slot| slot as u16).collect()
+        }
+
+        let txn = self.read_transaction();
+        let chain_info = self
+            .chain_store
+            .get_chain_info(hash, true, Some(&txn))
+            .ok()?;
+        let block_number = chain_info.head.block_number();
+
+        let disabled_slots = if block_number > Policy::genesis_block_number() {
+            let macro_block = self
+                .chain_store
+                .get_block_at(Policy::macro_block_before(block_number), true, Some(&txn))
+                .ok()?;
+            to_slots(
+                &macro_block
+                    .unwrap_macro()
+                    .body?
+                    .next_batch_initial_punished_set,
+            )
+        } else {
+            vec![]
+        };
+
+        let next_batch_punished_slots = match &chain_info.head {
+            Block::Macro(block) => block
+                .body
+                .as_ref()
+                .map(|body| to_slots(&body.next_batch_initial_punished_set)),
+            Block::Micro(_) => None,
+        };
+
+        Some(ChainInfoExport {
+            block_number,
+            batch_number: chain_info.head.batch_number(),
+            epoch_number: chain_info.head.epoch_number(),
+            on_main_chain: chain_info.on_main_chain,
+            cum_tx_fees: chain_info.cum_tx_fees,
+            disabled_slots,
+            next_batch_punished_slots,
+        })
+    }
+
+    /// Returns the number of confirmations of the main chain block with the given hash, i.e. the
+    /// number of main chain blocks from that block up to and including the head. The head itself
+    /// has one confirmation. Returns `None` if the block is unknown or not on the main chain.
+    pub fn confirmations(&self, hash: &Blake2bHash) -> Option<u32> {
+        let chain_info = self.chain_store.get_chain_info(hash, false, None).ok()?;
+        if !chain_info.on_main_chain {
+            return None;
+        }
+        Some(self.block_number() - chain_info.head.block_number() + 1)
+    }
+
+    /// Walks back from the head over at most `search_depth` blocks and returns the hash of the
+    /// first block whose state root matches the given one.
+    pub fn find_block_by_state_root(
+        &self,
+        state_root: &Blake2bHash,
+        search_depth: u32,
+    ) -> Option<Blake2bHash> {
+        let txn = self.read_transaction();
+
+        let mut block = self.head();
+        for _ in 0..search_depth {
+            if block.state_root() == state_root {
+                return Some(block.hash());
+            }
+
+            if block.block_number() <= self.get_genesis_block_number() {
+                break;
+            }
+
+            block = self
+                .chain_store
+                .get_block(block.parent_hash(), false, Some(&txn))
+                .ok()?;
+        }
+
+        None
+    }
+
+    /// Returns the number of consecutive skip blocks on the main chain that end at the given
+    /// block height, i.e. how many times in a row the block production had to be skipped up to
+    /// and including that height. Returns zero if the block at that height is not a skip block
+    /// and `None` if there is no block at that height.
+    pub fn consecutive_skip_blocks_at(&self, block_number: u32) -> Option<u32> {
+        let txn = self.read_transaction();
+
+        let mut count = 0;
+        let mut block = self
+            .chain_store
+            .get_block_at(block_number, false, Some(&txn))
+            .ok()?;
+        while block.is_skip() {
+            count += 1;
+            block = self
+                .chain_store
+                .get_block(block.parent_hash(), false, Some(&txn))
+                .ok()?;
+        }
+
+        Some(count)
+    }
+
+    /// Returns the hashes of the micro blocks between the macro head and the head of the main
+    /// chain, in ascending order. These are the blocks that can still be reverted.
+    pub fn micro_blocks_since_macro(&self) -> Vec<Blake2bHash> {
+        let txn = self.read_transaction();
+        let macro_block_number = self.state.macro_info.head.block_number();
+
+        let mut hashes = vec![];
+        let mut hash = self.state.head_hash.clone();
+        let mut block = self.state.main_chain.head.clone();
+        while block.block_number() > macro_block_number {
+            let parent_hash = block.parent_hash().clone();
+            hashes.push(hash);
+            block = self
+                .chain_store
+                .get_block(&parent_hash, false, Some(&txn))
+                .expect("Failed to retrieve main chain block");
+            hash = parent_hash;
+        }
+
+        hashes.reverse();
+        hashes
+    }
+
+    /// Returns a proof that the election block with the given hash is part of the main chain. The
+    /// proof consists of the election blocks that link our election head to the given block via
+    /// their interlinks.
+    /// Only election blocks can be proven this way, since only they are linked via interlinks.
+    /// Other blocks of the main chain, i.e. micro blocks and checkpoint blocks, are rejected with
+    /// `BlockchainError::BlockIsNotElection`. Unknown blocks are rejected with
+    /// `BlockchainError::BlockNotFound` and blocks that are not on the main chain with
+    /// `BlockchainError::BlockNotOnMainChain`.
+    pub fn main_chain_proof(
+        &self,
+        hash: &Blake2bHash,
+    ) -> Result<BlockInclusionProof, BlockchainError> {
+        let txn = self.read_transaction();
+
+        let chain_info = self.chain_store.get_chain_info(hash, false, Some(&txn))?;
+        if !chain_info.on_main_chain {
+            return Err(BlockchainError::BlockNotOnMainChain);
+        }
+        if !chain_info.head.is_election() {
+            return Err(BlockchainError::BlockIsNotElection);
+        }
+
+        let hops = BlockInclusionProof::get_interlink_hops(
+            chain_info.head.block_number(),
+            self.election_head().block_number(),
+        );
+
+        let mut proof = vec![];
+        for hop in hops {
+            match self.chain_store.get_block_at(hop, false, Some(&txn))? {
+                Block::Macro(block) => proof.push(block),
+                Block::Micro(_) => return Err(BlockchainError::BlockIsNotMacro),
+            }
+        }
+
+        Ok(BlockInclusionProof { proof })
+    }
+
     /// Returns information about the proposer at the given block height and offset.
     /// The offset is the block number for micro blocks + skip blocks and the round number for macro blocks.
     pub fn get_proposer_at(
@@ -102,6 +640,18 @@ impl Blockchain {
         )
     }
 
+    /// Returns the slot that owns the block following the current head at the given offset,
+    /// together with the uncompressed voting key of the validator owning it.
+    /// The offset is interpreted as in `get_proposer_at`.
+    pub fn next_block_producer(
+        &self,
+        offset: u32,
+    ) -> Result<(Slot, BlsPublicKey), BlockchainError> {
+        let slot = self.get_proposer_at(self.block_number() + 1, offset, None)?;
+        let voting_key = *slot.validator.voting_key.uncompress_unchecked();
+        Ok((slot, voting_key))
+    }
+
     pub fn get_macro_blocks(
         &self,
         start_block_hash: &Blake2bHash,
@@ -121,6 +671,29 @@ impl Blockchain {
         )
     }
 
+    /// Returns the macro block that finalizes the batch the given block number belongs to. If the
+    /// block number is a macro block itself, that block is returned.
+    /// Returns `None` if the macro block hasn't been produced yet.
+    pub fn macro_block_for_height(
+        &self,
+        block_number: u32,
+        include_body: bool,
+    ) -> Option<MacroBlock> {
+        let macro_block_number = Policy::macro_block_of(Policy::batch_at(block_number))?;
+        if macro_block_number > self.macro_head().block_number() {
+            return None;
+        }
+
+        match self
+            .chain_store
+            .get_block_at(macro_block_number, include_body, None)
+            .ok()?
+        {
+            Block::Macro(macro_block) => Some(macro_block),
+            Block::Micro(_) => None,
+        }
+    }
+
     /// Returns the current staking contract.
     pub fn get_staking_contract(&self) -> StakingContract {
         self.get_staking_contract_if_complete(None)
@@ -143,6 +716,37 @@ impl Blockchain {
         }
     }
 
+    /// Returns the total active stake backing the current validator set, i.e. the sum of the
+    /// balances of all current validators that are still active in the staking contract.
+    /// Returns `None` if the staking contract is not available.
+    pub fn total_active_stake(&self) -> Option<Coin> {
+        let staking_contract = self.get_staking_contract_if_complete(None)?;
+        let validators = self.current_validators()?;
+
+        Some(
+            validators
+                .iter()
+                .filter_map(|validator| staking_contract.active_validators.get(&validator.address))
+                .copied()
+                .sum(),
+        )
+    }
+
+    /// Returns the slots that were punished in the last finalized batch. These slots don't receive
+    /// a reward for that batch, instead their reward is burned when the batch rewards are
+    /// distributed in the next macro block.
+    /// Returns `None` if the staking contract is not available.
+    pub fn finalized_batch_punished_set(&self) -> Option<BitSet> {
+        let staking_contract = self.get_staking_contract_if_complete(None)?;
+
+        Some(
+            staking_contract
+                .punished_slots
+                .previous_batch_punished_slots()
+                .clone(),
+        )
+    }
+
     /// Returns the contract data store for the staking contract.
     pub fn get_staking_contract_store(&self) -> DataStore {
         self.state
@@ -164,6 +768,13 @@ impl Blockchain {
         }
     }
 
+    /// Returns the account at the given address, read through the given reader instead of the
+    /// live accounts. The reader must be rooted at the accounts trie, e.g. a read handle of
+    /// `Accounts::root_data_store`. Returns the default (empty) account if there is none.
+    pub fn get_account_with<R: DataStoreReadOps>(&self, address: &Address, reader: &R) -> Account {
+        reader.get(&KeyNibbles::from(address)).unwrap_or_default()
+    }
+
     /// The given account must correspond to the sender of the given transaction.
     pub fn reserve_balance(
         &self,
@@ -207,6 +818,184 @@ impl Blockchain {
             .tx_in_validity_window(tx_hash, max_block_number, txn_opt)
     }
 
+    /// Calls `f` for each historic transaction of the given epoch, in order, without collecting
+    /// all transactions of the epoch first.
+    pub fn for_each_epoch_transaction(&self, epoch: u32, mut f: impl FnMut(&HistoricTransaction)) {
+        self.history_store
+            .for_each_epoch_transaction(epoch, None, &mut f);
+    }
+
+    /// Returns at most `limit` historic transactions of the given epoch, starting at the
+    /// transaction with index `offset`, in the same order as `get_epoch_transactions` of the
+    /// history store. Transactions before `offset` are not loaded.
+    /// Returns `None` if the epoch is not in the history store.
+    pub fn epoch_transactions_paged(
+        &self,
+        epoch: u32,
+        offset: usize,
+        limit: usize,
+    ) -> Option<Vec<HistoricTransaction>> {
+        // The light history store only keeps the validity window, not the history of full epochs.
+        if self.config.light_history_store {
+            return None;
+        }
+
+        let txn = self.read_transaction();
+
+        // The history store range is given in block numbers.
+        let (first_block, last_block) = self.history_store.history_store_range(Some(&txn));
+        if epoch < Policy::epoch_at(first_block) || epoch > Policy::epoch_at(last_block) {
+            return None;
+        }
+
+        Some(
+            self.history_store
+                .get_epoch_transactions_paged(epoch, offset, limit, Some(&txn)),
+        )
+    }
+
+    /// Computes the history root that results from adding the given historic transactions at the
+    /// given block number on top of the current history tree. The history store is left unchanged.
+    /// Returns `None` if the transactions can't be added to the history tree.
+    pub fn compute_history_root(
+        &self,
+        block_number: u32,
+        hist_txs: &[HistoricTransaction],
+    ) -> Option<Blake2bHash> {
+        let mut txn = self.write_transaction();
+
+        let history_root = self
+            .history_store
+            .add_to_history(&mut txn, block_number, hist_txs)
+            .map(|(root, _)| root);
+
+        txn.abort();
+        history_root
+    }
+
+    /// Returns an inclusion proof for all transactions in the given block that have a sender or
+    /// recipient within the given set of addresses. The proof can be verified against the
+    /// history root of that block.
+    /// Returns `None` if the block is unknown, not on the main chain or has no micro body stored.
+    pub fn get_transactions_proof(
+        &self,
+        block_hash: &Blake2bHash,
+        addresses: &HashSet<Address>,
+    ) -> Option<HistoryTreeProof> {
+        let txn = self.read_transaction();
+
+        let chain_info = self
+            .chain_store
+            .get_chain_info(block_hash, true, Some(&txn))
+            .ok()?;
+        if !chain_info.on_main_chain {
+            return None;
+        }
+
+        let hashes: Vec<Blake2bHash> = chain_info
+            .head
+            .transactions()?
+            .iter()
+            .filter(|tx| {
+                let tx = tx.get_raw_transaction();
+                addresses.contains(&tx.sender) || addresses.contains(&tx.recipient)
+            })
+            .map(|tx| tx.raw_tx_hash().into())
+            .collect();
+
+        // Prove against the history tree as it was right after the given block.
+        self.history_store.prove(
+            chain_info.head.epoch_number(),
+            hashes.iter().collect(),
+            Some(chain_info.history_tree_len as usize),
+            Some(&txn),
+        )
+    }
+
+    /// Returns the block with the given hash together with an inclusion proof for all of its
+    /// historic transactions, i.e. its transactions and inherents. The proof can be verified
+    /// against the history root of the block.
+    /// Returns `None` if the block is unknown, not on the main chain or has no body stored.
+    pub fn get_block_with_history_proof(
+        &self,
+        block_hash: &Blake2bHash,
+    ) -> Option<(Block, HistoryTreeProof)> {
+        let txn = self.read_transaction();
+
+        let chain_info = self
+            .chain_store
+            .get_chain_info(block_hash, true, Some(&txn))
+            .ok()?;
+        if !chain_info.on_main_chain || chain_info.head.body().is_none() {
+            return None;
+        }
+
+        let hashes: Vec<Blake2bHash> = self
+            .history_store
+            .get_block_transactions(chain_info.head.block_number(), Some(&txn))
+            .iter()
+            .map(|hist_tx| hist_tx.tx_hash().into())
+            .collect();
+
+        // Prove against the history tree as it was right after the given block.
+        let proof = self.history_store.prove(
+            chain_info.head.epoch_number(),
+            hashes.iter().collect(),
+            Some(chain_info.history_tree_len as usize),
+            Some(&txn),
+        )?;
+
+        Some((chain_info.head, proof))
+    }
+
+    /// Returns the receipts (transaction hash and block number) of the most recent basic
+    /// transactions involving the given address, newest first. At most `sender_limit` transactions
+    /// sent by the address and `recipient_limit` transactions received by it are returned.
+    pub fn get_transaction_receipts_by_address(
+        &self,
+        address: &Address,
+        sender_limit: usize,
+        recipient_limit: usize,
+    ) -> Vec<(Blake2bHash, u32)> {
+        let txn = self.read_transaction();
+
+        let mut receipts = vec![];
+        let mut num_sent = 0;
+        let mut num_received = 0;
+
+        let tx_hashes = self
+            .history_store
+            .get_tx_hashes_by_address(address, u16::MAX, Some(&txn));
+
+        for tx_hash in tx_hashes {
+            if num_sent >= sender_limit && num_received >= recipient_limit {
+                break;
+            }
+
+            for hist_tx in self.history_store.get_hist_tx_by_hash(&tx_hash, Some(&txn)) {
+                if hist_tx.is_not_basic() {
+                    continue;
+                }
+
+                let tx = hist_tx.unwrap_basic().get_raw_transaction();
+                let is_sender = tx.sender == *address && num_sent < sender_limit;
+                let is_recipient = tx.recipient == *address && num_received < recipient_limit;
+
+                if is_sender {
+                    num_sent += 1;
+                }
+                if is_recipient {
+                    num_received += 1;
+                }
+                if is_sender || is_recipient {
+                    receipts.push((hist_tx.tx_hash().into(), hist_tx.block_number));
+                }
+            }
+        }
+
+        receipts
+    }
+
     pub fn staking_contract_address(&self) -> Address {
         Policy::STAKING_CONTRACT_ADDRESS
     }
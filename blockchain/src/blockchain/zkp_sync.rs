@@ -1,4 +1,4 @@
-use std::{cmp, mem};
+use std::{cmp, mem, sync::Arc};
 
 use nimiq_block::{Block, BlockError};
 use nimiq_blockchain_interface::{
@@ -129,7 +129,9 @@ impl Blockchain {
             }
 
             let new_slots = macro_block.get_validators().unwrap();
-            this.state.current_slots.replace(new_slots);
+            this.state.current_slots.replace(Arc::new(new_slots));
+
+            this.invalidate_slots_cache();
         }
 
         this.state.main_chain = chain_info;
@@ -209,7 +211,7 @@ impl Blockchain {
 
         // Store the election block header.
         if let Block::Macro(ref macro_block) = block {
-            this.state.previous_slots = macro_block.get_validators();
+            this.state.previous_slots = macro_block.get_validators().map(Arc::new);
         }
 
         debug!(
@@ -316,7 +318,7 @@ impl Blockchain {
                 this.state.previous_slots.replace(old_slots);
 
                 let new_slots = macro_block.get_validators().unwrap();
-                this.state.current_slots.replace(new_slots);
+                this.state.current_slots.replace(Arc::new(new_slots));
             }
         }
 
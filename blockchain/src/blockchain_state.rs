@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use nimiq_account::Accounts;
 use nimiq_block::MacroBlock;
 use nimiq_blockchain_interface::ChainInfo;
@@ -21,8 +23,25 @@ pub struct BlockchainState {
     pub election_head: MacroBlock,
     /// The hash of the last election macro block.
     pub election_head_hash: Blake2bHash,
-    /// The validator slots for the current epoch.
-    pub current_slots: Option<Validators>,
-    /// The validator slots for the previous epoch.
-    pub previous_slots: Option<Validators>,
+    /// The validator slots for the current epoch. They are shared, so handing them out is cheap.
+    pub current_slots: Option<Arc<Validators>>,
+    /// The validator slots for the previous epoch. They are shared, so handing them out is cheap.
+    pub previous_slots: Option<Arc<Validators>>,
+}
+
+/// A consistent snapshot of the head of the blockchain, as returned by `Blockchain::head_info`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeadInfo {
+    /// The hash of the head of the main chain.
+    pub hash: Blake2bHash,
+    /// The block number of the head of the main chain.
+    pub block_number: u32,
+    /// Whether the head of the main chain is a skip block.
+    pub is_skip: bool,
+    /// The timestamp of the head of the main chain.
+    pub timestamp: u64,
+    /// The hash of the last macro block.
+    pub macro_head_hash: Blake2bHash,
+    /// The hash of the last election macro block.
+    pub election_head_hash: Blake2bHash,
 }
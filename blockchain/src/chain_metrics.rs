@@ -3,14 +3,32 @@ use nimiq_blockchain_interface::{ChunksPushError, ChunksPushResult, PushError, P
 use nimiq_hash::Blake2bHash;
 use prometheus_client::{
     encoding::{EncodeLabelSet, EncodeLabelValue},
-    metrics::{counter::Counter, family::Family},
+    metrics::{
+        counter::Counter,
+        family::Family,
+        histogram::{exponential_buckets, Histogram},
+    },
     registry::Registry,
 };
 
-#[derive(Default)]
 pub struct BlockchainMetrics {
     block_push_counts: Family<PushResultLabels, Counter>,
     transactions_counts: Family<TransactionProcessedLabels, Counter>,
+    rebranch_reverted_blocks: Histogram,
+    rebranch_adopted_blocks: Histogram,
+    rebranch_depth: Histogram,
+}
+
+impl Default for BlockchainMetrics {
+    fn default() -> Self {
+        Self {
+            block_push_counts: Default::default(),
+            transactions_counts: Default::default(),
+            rebranch_reverted_blocks: Histogram::new(exponential_buckets(1.0, 2.0, 10)),
+            rebranch_adopted_blocks: Histogram::new(exponential_buckets(1.0, 2.0, 10)),
+            rebranch_depth: Histogram::new(exponential_buckets(1.0, 2.0, 10)),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
@@ -53,6 +71,24 @@ impl BlockchainMetrics {
             "Count of transactions applied/reverted",
             self.transactions_counts.clone(),
         );
+
+        registry.register(
+            "rebranch_reverted_blocks",
+            "Number of blocks reverted by a rebranch",
+            self.rebranch_reverted_blocks.clone(),
+        );
+
+        registry.register(
+            "rebranch_adopted_blocks",
+            "Number of blocks adopted by a rebranch",
+            self.rebranch_adopted_blocks.clone(),
+        );
+
+        registry.register(
+            "rebranch_depth",
+            "Depth of the common ancestor of a rebranch below the previous head",
+            self.rebranch_depth.clone(),
+        );
     }
 
     #[inline]
@@ -100,7 +136,14 @@ impl BlockchainMetrics {
         &self,
         reverted_blocks: &[(Blake2bHash, Block)],
         adopted_blocks: &[(Blake2bHash, Block)],
+        common_ancestor_depth: u32,
     ) {
+        self.rebranch_reverted_blocks
+            .observe(reverted_blocks.len() as f64);
+        self.rebranch_adopted_blocks
+            .observe(adopted_blocks.len() as f64);
+        self.rebranch_depth.observe(common_ancestor_depth as f64);
+
         for (_, micro_block) in reverted_blocks {
             if let Some(Micro(micro_body)) = micro_block.body() {
                 self.transactions_counts
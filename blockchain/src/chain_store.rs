@@ -32,6 +32,7 @@ impl ChainStore {
     const ACCOUNTS_DIFF_DB_NAME: &'static str = "AccountsDiff";
 
     const HEAD_KEY: &'static str = "head";
+    const PRUNED_BODIES_KEY: &'static str = "pruned_bodies";
 
     pub fn new(db: DatabaseProxy) -> Self {
         let chain_table = db.open_table(Self::CHAIN_DB_NAME.to_string());
@@ -75,6 +76,22 @@ impl ChainStore {
         txn.put(&self.chain_table, ChainStore::HEAD_KEY, hash);
     }
 
+    /// Returns the block number below which the bodies of main chain micro blocks have already
+    /// been pruned, if any were pruned.
+    pub fn get_pruned_bodies_height(&self, txn_option: Option<&TransactionProxy>) -> Option<u32> {
+        match txn_option {
+            Some(txn) => txn.get(&self.chain_table, ChainStore::PRUNED_BODIES_KEY),
+            None => self
+                .db
+                .read_transaction()
+                .get(&self.chain_table, ChainStore::PRUNED_BODIES_KEY),
+        }
+    }
+
+    pub fn set_pruned_bodies_height(&self, txn: &mut WriteTransactionProxy, height: u32) {
+        txn.put(&self.chain_table, ChainStore::PRUNED_BODIES_KEY, &height);
+    }
+
     pub fn get_chain_info(
         &self,
         hash: &Blake2bHash,
@@ -229,6 +246,16 @@ impl ChainStore {
         txn.remove_item(&self.height_idx, &height, hash);
     }
 
+    /// Removes the stored body of the block with the given hash while keeping its chain info, so
+    /// that the block header remains available. Returns `true` if a body was removed.
+    pub fn remove_block_body(&self, txn: &mut WriteTransactionProxy, hash: &Blake2bHash) -> bool {
+        if txn.get::<_, Block>(&self.block_table, hash).is_none() {
+            return false;
+        }
+        txn.remove(&self.block_table, hash);
+        true
+    }
+
     pub fn get_block(
         &self,
         hash: &Blake2bHash,
@@ -305,6 +332,33 @@ impl ChainStore {
             .collect()
     }
 
+    /// Returns the hash of the main chain block at the given height without loading the block body.
+    pub fn get_block_hash_at(
+        &self,
+        block_height: u32,
+        txn_option: Option<&TransactionProxy>,
+    ) -> Option<Blake2bHash> {
+        let read_txn: TransactionProxy;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = self.db.read_transaction();
+                &read_txn
+            }
+        };
+
+        let cursor = txn.cursor(&self.height_idx);
+        cursor
+            .into_iter_dup_of::<u32, Blake2bHash>(&block_height)
+            .map(|(_height, hash)| hash)
+            .find(|hash| {
+                let chain_info: ChainInfo = txn
+                    .get(&self.chain_table, hash)
+                    .expect("Corrupted store: ChainInfo referenced from index not found");
+                chain_info.on_main_chain
+            })
+    }
+
     pub fn get_blocks_at(
         &self,
         block_height: u32,
@@ -361,12 +415,19 @@ impl ChainStore {
 
         let mut hash = start_block.parent_hash().clone();
         while (blocks.len() as u32) < count {
-            if let Ok(block) = self.get_block(&hash, include_body, Some(txn)) {
-                hash = block.parent_hash().clone();
-                blocks.push(block);
-            } else {
+            let Ok(block) = self.get_block(&hash, false, Some(txn)) else {
                 break;
-            }
+            };
+
+            // Fail if the body was requested but has been pruned.
+            let block = if include_body {
+                self.get_block(&hash, true, Some(txn))?
+            } else {
+                block
+            };
+
+            hash = block.parent_hash().clone();
+            blocks.push(block);
         }
 
         Ok(blocks)
@@ -393,14 +454,20 @@ impl ChainStore {
         let mut chain_info = self.get_chain_info(start_block_hash, false, Some(txn))?;
 
         while (blocks.len() as u32) < count {
-            if let Some(ref successor) = chain_info.main_chain_successor {
-                let chain_info_opt = self.get_chain_info(successor, include_body, Some(txn));
+            if let Some(successor) = chain_info.main_chain_successor.clone() {
+                let chain_info_opt = self.get_chain_info(&successor, false, Some(txn));
                 if chain_info_opt.is_err() {
                     break;
                 }
 
                 chain_info = chain_info_opt.unwrap();
-                blocks.push(chain_info.head);
+
+                // Fail if the body was requested but has been pruned.
+                if include_body {
+                    blocks.push(self.get_block(&successor, true, Some(txn))?);
+                } else {
+                    blocks.push(chain_info.head);
+                }
             } else {
                 break;
             }
@@ -599,6 +666,20 @@ impl ChainStore {
         }
     }
 
+    /// Removes the revert infos of all blocks up to and including the given block height.
+    pub fn prune_revert_infos(&self, txn: &mut WriteTransactionProxy, block_height: u32) {
+        let mut cursor = WriteTransaction::cursor(txn, &self.revert_table);
+        let mut pos: Option<(u32, RevertInfo)> = cursor.first();
+
+        while let Some((height, _)) = pos {
+            if height > block_height {
+                break;
+            }
+            cursor.remove();
+            pos = cursor.next();
+        }
+    }
+
     pub fn put_accounts_diff(
         &self,
         txn: &mut WriteTransactionProxy,
@@ -458,6 +458,62 @@ impl HistoryStore {
         // Return the history root.
         tree.get_root().ok()
     }
+
+    /// Gets all historic transactions for a given finalized epoch. The leaves of the history tree
+    /// are split into chunks that are fetched in parallel, each within its own read transaction.
+    /// The transactions are returned in the same order as by `get_epoch_transactions`.
+    /// Returns `None` if the epoch is not finalized yet, since its history tree could then change
+    /// between the read transactions of the chunks.
+    #[cfg(feature = "parallel")]
+    fn get_epoch_transactions_parallel(
+        &self,
+        epoch_number: u32,
+    ) -> Option<Vec<HistoricTransaction>> {
+        use rayon::prelude::*;
+
+        const CHUNK_SIZE: usize = 256;
+
+        let num_leaves = {
+            let txn = self.db.read_transaction();
+
+            // The epoch is finalized once the history contains its election block or any later
+            // block. Only micro blocks after the last macro block can be reverted.
+            let election_block = Policy::election_block_of(epoch_number)?;
+            if self.get_last_leaf_block_number(Some(&txn))? < election_block {
+                return None;
+            }
+
+            MerkleMountainRange::new(MMRStore::with_read_transaction(
+                &self.hist_tree_table,
+                &txn,
+                epoch_number,
+            ))
+            .num_leaves()
+        };
+
+        let chunks: Vec<Vec<HistoricTransaction>> = (0..num_leaves)
+            .step_by(CHUNK_SIZE)
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|start| {
+                let txn = self.db.read_transaction();
+                let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(
+                    &self.hist_tree_table,
+                    &txn,
+                    epoch_number,
+                ));
+
+                (start..cmp::min(start + CHUNK_SIZE, num_leaves))
+                    .map(|i| {
+                        let leaf_hash = tree.get_leaf(i).unwrap();
+                        self.get_historic_tx(&leaf_hash, Some(&txn)).unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Some(chunks.concat())
+    }
 }
 
 impl HistoryInterface for HistoryStore {
@@ -757,11 +813,20 @@ impl HistoryInterface for HistoryStore {
     }
 
     /// Gets all historic transactions for a given epoch.
+    /// With the `parallel` feature and without a given transaction, the historic transactions
+    /// of finalized epochs are fetched in parallel.
     fn get_epoch_transactions(
         &self,
         epoch_number: u32,
         txn_option: Option<&TransactionProxy>,
     ) -> Vec<HistoricTransaction> {
+        #[cfg(feature = "parallel")]
+        if txn_option.is_none() {
+            if let Some(hist_txs) = self.get_epoch_transactions_parallel(epoch_number) {
+                return hist_txs;
+            }
+        }
+
         let read_txn: TransactionProxy;
         let txn = match txn_option {
             Some(txn) => txn,
@@ -789,6 +854,71 @@ impl HistoryInterface for HistoryStore {
         hist_txs
     }
 
+    /// Calls `f` for each historic transaction of a given epoch, in order.
+    fn for_each_epoch_transaction(
+        &self,
+        epoch_number: u32,
+        txn_option: Option<&TransactionProxy>,
+        f: &mut dyn FnMut(&HistoricTransaction),
+    ) {
+        let read_txn: TransactionProxy;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = self.db.read_transaction();
+                &read_txn
+            }
+        };
+
+        // Get history tree for given epoch.
+        let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(
+            &self.hist_tree_table,
+            txn,
+            epoch_number,
+        ));
+
+        // Get each historic transaction from the tree and pass it on.
+        for i in 0..tree.num_leaves() {
+            let leaf_hash = tree.get_leaf(i).unwrap();
+            f(&self.get_historic_tx(&leaf_hash, Some(txn)).unwrap());
+        }
+    }
+
+    /// Gets at most `limit` historic transactions of a given epoch, starting at the transaction
+    /// with index `offset`. Only the requested leaves of the history tree are read.
+    fn get_epoch_transactions_paged(
+        &self,
+        epoch_number: u32,
+        offset: usize,
+        limit: usize,
+        txn_option: Option<&TransactionProxy>,
+    ) -> Vec<HistoricTransaction> {
+        let read_txn: TransactionProxy;
+        let txn = match txn_option {
+            Some(txn) => txn,
+            None => {
+                read_txn = self.db.read_transaction();
+                &read_txn
+            }
+        };
+
+        // Get history tree for given epoch.
+        let tree = MerkleMountainRange::new(MMRStore::with_read_transaction(
+            &self.hist_tree_table,
+            txn,
+            epoch_number,
+        ));
+
+        // Get each requested historic transaction from the tree.
+        let end = offset.saturating_add(limit).min(tree.num_leaves());
+        (offset..end)
+            .map(|i| {
+                let leaf_hash = tree.get_leaf(i).unwrap();
+                self.get_historic_tx(&leaf_hash, Some(txn)).unwrap()
+            })
+            .collect()
+    }
+
     /// Returns the number of historic transactions for a given epoch.
     fn num_epoch_transactions(
         &self,
@@ -1738,6 +1868,74 @@ mod tests {
         assert_eq!(query[1].unwrap_reward().value, Coin::from_u64_unchecked(4));
     }
 
+    #[test]
+    fn for_each_epoch_transaction_works() {
+        // Initialize History Store.
+        let env = VolatileDatabase::new(20).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+
+        // Add historic transactions to History Store.
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 0, &hist_txs[..3]);
+        history_store.add_to_history(&mut txn, Policy::genesis_block_number() + 1, &hist_txs[3..]);
+
+        // Verify method works.
+        for epoch_number in 0..=2 {
+            let mut visited = vec![];
+            history_store.for_each_epoch_transaction(epoch_number, Some(&txn), &mut |hist_tx| {
+                visited.push(hist_tx.clone())
+            });
+
+            let query = history_store.get_epoch_transactions(epoch_number, Some(&txn));
+            assert_eq!(visited.len(), query.len());
+            assert_eq!(visited, query);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn get_epoch_transactions_in_parallel_works() {
+        // Initialize History Store.
+        let env = VolatileDatabase::new(20).unwrap();
+        let history_store = HistoryStore::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Add enough historic transactions to the first epoch to span several chunks.
+        let mut txn = env.write_transaction();
+        for block_number in 1..=6 {
+            let block_number = Policy::genesis_block_number() + block_number;
+            let hist_txs: Vec<_> = (0..100)
+                .map(|i| create_transaction(block_number, u64::from(block_number) * 100 + i))
+                .collect();
+            history_store.add_to_history(&mut txn, block_number, &hist_txs);
+        }
+        txn.commit();
+
+        // The first epoch is not finalized yet, so it isn't fetched in parallel.
+        assert!(history_store.get_epoch_transactions_parallel(1).is_none());
+        assert_eq!(history_store.get_epoch_transactions(1, None).len(), 600);
+
+        // Finalize the first epoch by adding history of the second epoch.
+        let mut txn = env.write_transaction();
+        let block_number = Policy::election_block_of(1).unwrap() + 1;
+        history_store.add_to_history(
+            &mut txn,
+            block_number,
+            &[create_transaction(block_number, 1)],
+        );
+        txn.commit();
+
+        // The parallel fetch returns the same transactions in the same order.
+        let read_txn = env.read_transaction();
+        let sequential = history_store.get_epoch_transactions(1, Some(&read_txn));
+        let parallel = history_store.get_epoch_transactions_parallel(1).unwrap();
+        assert_eq!(sequential.len(), 600);
+        assert_eq!(sequential, parallel);
+        assert_eq!(history_store.get_epoch_transactions(1, None), sequential);
+    }
+
     #[test]
     fn get_num_historic_transactions_works() {
         // Initialize History Store.
@@ -109,6 +109,25 @@ pub trait HistoryInterface {
         txn_option: Option<&TransactionProxy>,
     ) -> Vec<HistoricTransaction>;
 
+    /// Calls `f` for each historic transaction of a given epoch, in order, without collecting
+    /// them first.
+    fn for_each_epoch_transaction(
+        &self,
+        epoch_number: u32,
+        txn_option: Option<&TransactionProxy>,
+        f: &mut dyn FnMut(&HistoricTransaction),
+    );
+
+    /// Gets at most `limit` historic transactions of a given epoch, starting at the transaction
+    /// with index `offset`, in the same order as `get_epoch_transactions`.
+    fn get_epoch_transactions_paged(
+        &self,
+        epoch_number: u32,
+        offset: usize,
+        limit: usize,
+        txn_option: Option<&TransactionProxy>,
+    ) -> Vec<HistoricTransaction>;
+
     /// Returns the number of historic transactions for a given epoch.
     fn num_epoch_transactions(
         &self,
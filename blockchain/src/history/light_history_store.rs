@@ -313,6 +313,25 @@ impl HistoryInterface for LightHistoryStore {
         unimplemented!()
     }
 
+    fn for_each_epoch_transaction(
+        &self,
+        _epoch_number: u32,
+        _txn_option: Option<&TransactionProxy>,
+        _f: &mut dyn FnMut(&HistoricTransaction),
+    ) {
+        unimplemented!()
+    }
+
+    fn get_epoch_transactions_paged(
+        &self,
+        _epoch_number: u32,
+        _offset: usize,
+        _limit: usize,
+        _txn_option: Option<&TransactionProxy>,
+    ) -> Vec<HistoricTransaction> {
+        unimplemented!()
+    }
+
     fn num_epoch_transactions(
         &self,
         epoch_number: u32,
@@ -1,8 +1,15 @@
 #[macro_use]
 extern crate log;
 
-pub use block_production::BlockProducer;
-pub use blockchain::blockchain::{Blockchain, BlockchainConfig, TransactionVerificationCache};
+pub use block_production::{micro_body_root, BlockProducer};
+pub use blockchain::blockchain::{
+    Blockchain, BlockchainConfig, TransactionFilter, TransactionVerificationCache,
+};
+pub use blockchain::slots::EpochPunishments;
+#[cfg(feature = "json")]
+pub use blockchain::slots::ValidatorSlotsJson;
+pub use blockchain::wrappers::ChainInfoExport;
+pub use blockchain_state::HeadInfo;
 pub use history::*;
 
 pub(crate) mod block_production;
@@ -1,7 +1,51 @@
 use std::convert::TryInto;
 
 use nimiq_block::MacroHeader;
-use nimiq_primitives::{coin::Coin, policy::Policy};
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
+
+/// The constants derived from the genesis block that are needed for reward and supply
+/// computations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenesisParameters {
+    /// The coin supply at the genesis block.
+    pub supply: Coin,
+    /// The timestamp of the genesis block.
+    pub timestamp: u64,
+    /// The hash of the genesis block.
+    pub hash: Blake2bHash,
+    /// The network ID of the genesis block.
+    pub network_id: NetworkId,
+}
+
+/// Determines what happens to a validator's batch reward if its reward address can't accept it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RewardFallback {
+    /// The reward is burned. This is the behavior of the public networks.
+    #[default]
+    Burn,
+    /// The reward is paid to the validator address instead. It is only burned if the validator
+    /// address can't accept it either.
+    ValidatorAddress,
+}
+
+/// A summary of the rewards that were distributed by a macro block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchRewardSummary {
+    /// The total reward of the batch, i.e. the block reward plus the transaction fees.
+    pub reward_pot: Coin,
+    /// The reward each eligible slot received.
+    pub slot_reward: Coin,
+    /// The part of the reward pot that couldn't be split evenly between the slots. It is given
+    /// to one randomly chosen validator.
+    pub remainder: Coin,
+    /// The reward that was burned, either because slots were punished or because the reward
+    /// address couldn't accept the reward.
+    pub burned_reward: Coin,
+    /// The reward paid out to each validator, including the remainder.
+    pub validator_rewards: Vec<(Address, Coin)>,
+}
 
 /// Parses the genesis supply and timestamp from the genesis block. We require both values to
 /// calculate the block rewards.
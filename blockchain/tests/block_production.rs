@@ -1,7 +1,7 @@
 use std::{convert::TryInto, sync::Arc};
 
 use nimiq_block::{Block, ForkProof, MicroJustification};
-use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+use nimiq_blockchain::{micro_body_root, BlockProducer, Blockchain, BlockchainConfig};
 use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
 use nimiq_bls::KeyPair as BlsKeyPair;
 use nimiq_database::{mdbx::MdbxDatabase, traits::WriteTransaction, volatile::VolatileDatabase};
@@ -12,17 +12,20 @@ use nimiq_keys::{
     PrivateKey as SchnorrPrivateKey, SecureGenerate,
 };
 use nimiq_primitives::{coin::Coin, policy::Policy};
-use nimiq_serde::Deserialize;
+use nimiq_serde::{Deserialize, Serialize};
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     block_production::TemporaryBlockProducer,
     blockchain::{
-        fill_micro_blocks, fill_micro_blocks_with_txns, produce_macro_blocks, sign_macro_block,
-        signing_key, validator_address, voting_key,
+        fill_micro_blocks, fill_micro_blocks_with_txns, generate_transactions,
+        produce_macro_blocks, sign_macro_block, signing_key, validator_address, voting_key,
     },
+    test_custom_block::{next_micro_block, BlockConfig},
     test_rng::test_rng,
 };
-use nimiq_transaction::ExecutedTransaction;
+use nimiq_transaction::{
+    historic_transaction::HistoricTransaction, ExecutedTransaction, Transaction,
+};
 use nimiq_transaction_builder::TransactionBuilder;
 use nimiq_utils::time::OffsetTime;
 use parking_lot::RwLock;
@@ -157,6 +160,27 @@ fn it_can_produce_micro_blocks() {
     );
 }
 
+#[test]
+fn it_can_produce_empty_micro_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let block = temp_producer
+        .producer
+        .empty_micro_block(&temp_producer.blockchain.read());
+    assert_eq!(
+        block.block_number(),
+        temp_producer.blockchain.read().block_number() + 1
+    );
+    assert!(block.body.as_ref().unwrap().transactions.is_empty());
+    assert!(block.body.as_ref().unwrap().equivocation_proofs.is_empty());
+
+    assert_eq!(
+        temp_producer.push(Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
 #[test]
 fn it_can_produce_macro_blocks() {
     let time = Arc::new(OffsetTime::new());
@@ -375,6 +399,91 @@ fn it_can_produce_a_chain_with_txns() {
     }
 }
 
+#[test]
+fn it_can_compute_history_root_for_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 5, 1);
+    let block = temp_producer.next_block_no_push_with_txs(vec![], false, txs);
+
+    // Compute the history root for the block's transactions before pushing it.
+    let hist_txs = HistoricTransaction::from(
+        NetworkId::UnitAlbatross,
+        block.block_number(),
+        block.timestamp(),
+        block.transactions().unwrap().to_vec(),
+        vec![],
+        vec![],
+    );
+    let history_root = temp_producer
+        .blockchain
+        .read()
+        .compute_history_root(block.block_number(), &hist_txs)
+        .unwrap();
+
+    // The push path verifies the header's history root against its own history tree.
+    assert_eq!(temp_producer.push(block.clone()), Ok(PushResult::Extended));
+    assert_eq!(&history_root, block.history_root());
+    assert_eq!(
+        &history_root,
+        temp_producer.blockchain.read().head().history_root()
+    );
+}
+
+#[test]
+fn it_can_compute_micro_body_root() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 5, 1);
+    let block = temp_producer.next_block_no_push_with_txs(vec![], false, txs.clone());
+
+    // All generated transactions are executed successfully.
+    let executed_txs: Vec<_> = txs.into_iter().map(ExecutedTransaction::Ok).collect();
+    assert_eq!(&micro_body_root(&[], &executed_txs), block.body_root());
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+
+    // The body root of an empty block.
+    let block = temp_producer.next_block_no_push(vec![], false);
+    assert_eq!(&micro_body_root(&[], &[]), block.body_root());
+}
+
+#[test]
+fn it_can_compute_state_root_for_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+
+    for skip_block in [false, true] {
+        let block = if skip_block {
+            temp_producer.next_block_no_push(vec![], true)
+        } else {
+            let block_number = temp_producer.blockchain.read().block_number() + 1;
+            let txs =
+                generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 5, 1);
+            temp_producer.next_block_no_push_with_txs(vec![], false, txs)
+        };
+
+        let state_root = temp_producer
+            .blockchain
+            .read()
+            .compute_state_root_for(&block)
+            .unwrap();
+        assert_eq!(&state_root, block.state_root());
+
+        // The previewed root matches the state after actually extending the chain.
+        assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+        let blockchain = temp_producer.blockchain.read();
+        assert_eq!(&state_root, blockchain.head().state_root());
+        assert_eq!(
+            state_root,
+            blockchain.state.accounts.get_root_hash_assert(None)
+        );
+    }
+}
+
 #[test]
 fn it_can_revert_create_staker_transaction() {
     let time = Arc::new(OffsetTime::new());
@@ -1381,3 +1490,157 @@ fn ed25519_key_pair(secret_key: &str) -> SchnorrKeyPair {
         Deserialize::deserialize_from_vec(&hex::decode(secret_key).unwrap()[..]).unwrap();
     priv_key.into()
 }
+
+/// Creates basic transactions from the funded account, one for each of the given fees.
+fn transactions_with_fees(block_number: u32, fees: &[u64]) -> Vec<Transaction> {
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+    fees.iter()
+        .enumerate()
+        .map(|(i, fee)| {
+            TransactionBuilder::new_basic(
+                &key_pair,
+                Address::from([i as u8 + 1; 20]),
+                Coin::from_u64_unchecked(1),
+                Coin::from_u64_unchecked(*fee),
+                block_number,
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn it_can_limit_the_number_of_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+
+    let config = BlockConfig {
+        transactions: transactions_with_fees(block_number, &[3, 1, 5, 2, 4]),
+        max_transactions: Some(2),
+        ..Default::default()
+    };
+    let block = next_micro_block(
+        &temp_producer.producer.signing_key,
+        &temp_producer.blockchain.read(),
+        &config,
+    );
+
+    // The transactions with the highest fees are kept.
+    let mut fees: Vec<u64> = block
+        .body
+        .as_ref()
+        .unwrap()
+        .transactions
+        .iter()
+        .map(|tx| u64::from(tx.get_raw_transaction().fee))
+        .collect();
+    fees.sort_unstable();
+    assert_eq!(fees, vec![4, 5]);
+
+    assert_eq!(
+        temp_producer.push(Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+#[test]
+fn it_can_limit_the_size_of_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+
+    let transactions = transactions_with_fees(block_number, &[3, 1, 5, 2, 4]);
+    let tx_size = transactions[0].serialized_size();
+
+    let config = BlockConfig {
+        transactions,
+        max_body_size: Some(tx_size * 3 + tx_size / 2),
+        ..Default::default()
+    };
+    let block = next_micro_block(
+        &temp_producer.producer.signing_key,
+        &temp_producer.blockchain.read(),
+        &config,
+    );
+
+    // Only three transactions fit, the ones with the highest fees are kept.
+    let body = block.body.as_ref().unwrap();
+    let body_size: usize = body
+        .transactions
+        .iter()
+        .map(|tx| tx.get_raw_transaction().serialized_size())
+        .sum();
+    assert!(body_size <= tx_size * 3 + tx_size / 2);
+
+    let mut fees: Vec<u64> = body
+        .transactions
+        .iter()
+        .map(|tx| u64::from(tx.get_raw_transaction().fee))
+        .collect();
+    fees.sort_unstable();
+    assert_eq!(fees, vec![3, 4, 5]);
+
+    assert_eq!(
+        temp_producer.push(Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
+
+#[test]
+fn it_can_prefer_transactions_by_fee_per_byte() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+
+    // A large transaction with the highest fee, but the lowest fee per byte.
+    let key_pair = ed25519_key_pair(ACCOUNT_SECRET_KEY);
+    let large_tx = TransactionBuilder::new_basic_with_data(
+        &key_pair,
+        Address::from([0xff; 20]),
+        vec![0; 64],
+        Coin::from_u64_unchecked(1),
+        Coin::from_u64_unchecked(12),
+        block_number,
+        NetworkId::UnitAlbatross,
+    )
+    .unwrap();
+    let mut transactions = transactions_with_fees(block_number, &[10, 11]);
+    assert!(large_tx.fee_per_byte() < transactions[0].fee_per_byte());
+    transactions.push(large_tx);
+
+    let fees_in_block = |order_by_fee| {
+        let config = BlockConfig {
+            transactions: transactions.clone(),
+            max_transactions: Some(2),
+            order_by_fee,
+            ..Default::default()
+        };
+        let block = next_micro_block(
+            &temp_producer.producer.signing_key,
+            &temp_producer.blockchain.read(),
+            &config,
+        );
+
+        let mut fees: Vec<u64> = block
+            .body
+            .as_ref()
+            .unwrap()
+            .transactions
+            .iter()
+            .map(|tx| u64::from(tx.get_raw_transaction().fee))
+            .collect();
+        fees.sort_unstable();
+        (block, fees)
+    };
+
+    let (_, fees) = fees_in_block(false);
+    assert_eq!(fees, vec![11, 12]);
+
+    let (block, fees) = fees_in_block(true);
+    assert_eq!(fees, vec![10, 11]);
+
+    // The transactions are still in canonical order, so the block is valid.
+    assert_eq!(
+        temp_producer.push(Block::Micro(block)),
+        Ok(PushResult::Extended)
+    );
+}
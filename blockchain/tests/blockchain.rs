@@ -1,16 +1,34 @@
-use std::sync::Arc;
+use std::{collections::HashSet, str::FromStr, sync::Arc};
 
+use futures::StreamExt;
+use nimiq_account::{Account, BasicAccount, BlockState};
 use nimiq_block::{Block, BlockError};
-use nimiq_blockchain::Blockchain;
-use nimiq_blockchain_interface::{AbstractBlockchain, PushError, PushResult};
-use nimiq_hash::Hash;
-use nimiq_primitives::policy::Policy;
+use nimiq_blockchain::{reward::genesis_parameters, BlockProducer, Blockchain, BlockchainConfig};
+use nimiq_blockchain_interface::{
+    AbstractBlockchain, BlockchainError, BlockchainEvent, Direction, PushError, PushResult,
+};
+use nimiq_database::{
+    traits::{Database, WriteTransaction},
+    volatile::VolatileDatabase,
+};
+use nimiq_genesis::NetworkInfo;
+use nimiq_genesis_builder::GenesisBuilder;
+use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput, SerializeContent};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
+use nimiq_primitives::{coin::Coin, key_nibbles::KeyNibbles, networks::NetworkId, policy::Policy};
 use nimiq_tendermint::ProposalMessage;
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     block_production::TemporaryBlockProducer,
+    blockchain::{
+        generate_transactions, produce_macro_blocks, signing_key, validator_address, voting_key,
+        REWARD_KEY,
+    },
     test_custom_block::{finalize_macro_block, next_macro_block_proposal},
 };
+use nimiq_transaction::{historic_transaction::HistoricTransaction, Transaction};
+use nimiq_utils::time::OffsetTime;
+use parking_lot::RwLock;
 
 #[test]
 fn prune_epoch_micro_blocks() {
@@ -165,3 +183,1601 @@ fn can_detect_invalid_punished_set() {
         Err(PushError::InvalidBlock(BlockError::InvalidValidators))
     );
 }
+
+#[test]
+fn can_detect_election_block_without_validators() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let config = Default::default();
+
+    // Move blockchain to the end of the epoch.
+    for _ in 0..Policy::blocks_per_epoch() - 1 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    // Create an election block without validators.
+    let block = {
+        let blockchain = temp_producer.blockchain.read();
+
+        let height = blockchain.block_number() + 1;
+        assert!(Policy::is_election_block_at(height));
+
+        let mut macro_block_proposal =
+            next_macro_block_proposal(&temp_producer.producer.signing_key, &blockchain, &config);
+        macro_block_proposal.body.as_mut().unwrap().validators = None;
+        macro_block_proposal.header.body_root = macro_block_proposal.body.as_ref().unwrap().hash();
+
+        let block_hash = macro_block_proposal.hash_blake2s();
+
+        Block::Macro(finalize_macro_block(
+            &temp_producer.producer.voting_key,
+            ProposalMessage {
+                valid_round: None,
+                proposal: macro_block_proposal.header,
+                round: config.tendermint_round.unwrap_or(0),
+            },
+            macro_block_proposal.body.unwrap(),
+            block_hash,
+            &config,
+        ))
+    };
+
+    // The block is rejected before any state is touched.
+    let head_hash = temp_producer.blockchain.read().head_hash();
+    assert_eq!(
+        temp_producer.push(block),
+        Err(PushError::InvalidBlock(BlockError::InvalidValidators))
+    );
+    assert_eq!(temp_producer.blockchain.read().head_hash(), head_hash);
+}
+
+#[test]
+fn it_can_trust_macro_bodies() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let config = Default::default();
+
+    // The first batch doesn't distribute rewards, so move to the end of the second batch.
+    for _ in 0..2 * Policy::blocks_per_batch() - 1 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    // Create a macro block that pays out a reward to a different recipient than expected.
+    let block = {
+        let blockchain = temp_producer.blockchain.read();
+
+        let height = blockchain.block_number() + 1;
+        assert!(Policy::is_macro_block_at(height));
+
+        let mut macro_block =
+            next_macro_block_proposal(&temp_producer.producer.signing_key, &blockchain, &config);
+        let body = macro_block.body.as_mut().unwrap();
+        assert!(!body.transactions.is_empty());
+        body.transactions[0].recipient = Address::from([1u8; 20]);
+        macro_block.header.body_root = body.hash();
+
+        // Recompute the state and history roots to match the modified rewards.
+        let inherents = blockchain.create_macro_block_inherents(&macro_block);
+        let block_state = BlockState::new(height, macro_block.header.timestamp);
+        let (state_root, diff_root, _) = blockchain
+            .state
+            .accounts
+            .exercise_transactions(&[], &inherents, &block_state)
+            .unwrap();
+        macro_block.header.state_root = state_root;
+        macro_block.header.diff_root = diff_root;
+
+        let hist_txs = HistoricTransaction::from(
+            NetworkId::UnitAlbatross,
+            height,
+            macro_block.header.timestamp,
+            vec![],
+            inherents,
+            vec![],
+        );
+        macro_block.header.history_root =
+            blockchain.compute_history_root(height, &hist_txs).unwrap();
+
+        let block_hash = macro_block.hash_blake2s();
+
+        Block::Macro(finalize_macro_block(
+            &temp_producer.producer.voting_key,
+            ProposalMessage {
+                valid_round: None,
+                proposal: macro_block.header,
+                round: config.tendermint_round.unwrap_or(0),
+            },
+            macro_block.body.unwrap(),
+            block_hash,
+            &config,
+        ))
+    };
+
+    // By default, the reward transactions are recomputed and the block is rejected.
+    {
+        let blockchain = temp_producer.blockchain.read();
+        let txn = blockchain.read_transaction();
+        assert_eq!(
+            blockchain.verify_block_state_pre_commit(&block, &txn),
+            Err(PushError::InvalidBlock(
+                BlockError::InvalidRewardTransactions
+            ))
+        );
+    }
+    assert_eq!(
+        temp_producer.push(block.clone()),
+        Err(PushError::InvalidBlock(
+            BlockError::InvalidRewardTransactions
+        ))
+    );
+
+    // When trusting macro bodies, the reward transactions are not recomputed and the block is
+    // accepted with its body as given.
+    temp_producer.blockchain.write().config.trust_macro_bodies = true;
+    {
+        let blockchain = temp_producer.blockchain.read();
+        let txn = blockchain.read_transaction();
+        assert_eq!(
+            blockchain.verify_block_state_pre_commit(&block, &txn),
+            Ok(())
+        );
+    }
+    assert_eq!(temp_producer.push(block.clone()), Ok(PushResult::Extended));
+
+    let blockchain = temp_producer.blockchain.read();
+    let head = blockchain
+        .get_block(&block.hash(), true, None)
+        .unwrap()
+        .unwrap_macro();
+    assert_eq!(
+        head.body.unwrap().transactions[0].recipient,
+        Address::from([1u8; 20])
+    );
+}
+
+#[test]
+fn it_returns_genesis_parameters() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let genesis_block = NetworkInfo::from_network_id(NetworkId::UnitAlbatross).genesis_block();
+    let (supply, timestamp) = genesis_parameters(&genesis_block.unwrap_macro_ref().header);
+
+    let parameters = temp_producer.blockchain.read().genesis_parameters();
+    assert_eq!(parameters.supply, supply);
+    assert_eq!(parameters.timestamp, timestamp);
+    assert_eq!(parameters.hash, genesis_block.hash());
+    assert_eq!(parameters.network_id, NetworkId::UnitAlbatross);
+}
+
+#[test]
+fn it_computes_total_active_stake() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let blockchain = temp_producer.blockchain.read();
+
+    // The unit genesis only contains a single validator, so its total stake is the
+    // total active stake.
+    let staking_contract = blockchain.get_staking_contract();
+    let data_store = blockchain.get_staking_contract_store();
+    let db_txn = blockchain.read_transaction();
+    let validator = staking_contract
+        .get_validator(&data_store.read(&db_txn), &validator_address())
+        .unwrap();
+
+    assert_eq!(blockchain.total_active_stake(), Some(validator.total_stake));
+}
+
+#[test]
+fn it_computes_current_pk_tree_root() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize,
+    );
+
+    let blockchain = temp_producer.blockchain.read();
+    let election_block = blockchain.election_head();
+    assert_eq!(
+        election_block.block_number(),
+        Policy::genesis_block_number() + Policy::blocks_per_epoch()
+    );
+
+    // The election block's body root commits to the public key tree root of the validators it
+    // elects, which are the current validators.
+    let body = election_block.body.clone().unwrap();
+    assert_eq!(election_block.header.body_root, body.hash::<Blake2sHash>());
+
+    let mut body_content = vec![];
+    body.serialize_content::<_, Blake2sHash>(&mut body_content)
+        .unwrap();
+    let pk_tree_root = blockchain.current_pk_tree_root().unwrap();
+    assert_eq!(pk_tree_root.as_bytes(), &body_content[..Blake2sHash::len()]);
+}
+
+#[test]
+fn it_finds_blocks_by_state_root() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    // Include transactions in every block so that each block has a distinct state root.
+    let mut blocks = vec![];
+    for i in 0..4 {
+        let block_number = temp_producer.blockchain.read().block_number() + 1;
+        let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 1, i);
+        blocks.push(temp_producer.next_block_with_txs(vec![], false, txs));
+    }
+    let first_block = &blocks[0];
+
+    let blockchain = temp_producer.blockchain.read();
+    let head = blockchain.head();
+    assert_eq!(
+        blockchain.find_block_by_state_root(head.state_root(), 1),
+        Some(head.hash())
+    );
+
+    // The first block is only found if the search goes back far enough.
+    assert_eq!(
+        blockchain.find_block_by_state_root(first_block.state_root(), 4),
+        Some(first_block.hash())
+    );
+    assert_eq!(
+        blockchain.find_block_by_state_root(first_block.state_root(), 3),
+        None
+    );
+
+    assert_eq!(
+        blockchain.find_block_by_state_root(&Blake2bHash::default(), 100),
+        None
+    );
+}
+
+#[test]
+fn it_can_resync_slots() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize,
+    );
+
+    let mut blockchain = temp_producer.blockchain.write();
+    let current_slots = blockchain.state.current_slots.clone();
+    let previous_slots = blockchain.state.previous_slots.clone();
+    assert!(current_slots.is_some());
+    assert!(previous_slots.is_some());
+
+    // Corrupt the cached slots.
+    blockchain.state.current_slots = None;
+    blockchain.state.previous_slots = None;
+
+    assert_eq!(blockchain.resync_slots(), Ok(()));
+    assert_eq!(blockchain.state.current_slots, current_slots);
+    assert_eq!(blockchain.state.previous_slots, previous_slots);
+    assert_eq!(
+        blockchain.state.current_slots.as_deref().cloned(),
+        blockchain.election_head().get_validators()
+    );
+}
+
+#[test]
+fn it_can_resync_slots_at_genesis() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    {
+        let mut blockchain = temp_producer.blockchain.write();
+        let previous_slots = blockchain.state.previous_slots.clone();
+        assert!(previous_slots.is_some());
+
+        blockchain.state.current_slots = None;
+        blockchain.state.previous_slots = None;
+        assert_eq!(blockchain.resync_slots(), Ok(()));
+        assert_eq!(blockchain.state.previous_slots, previous_slots);
+    }
+
+    // The rewards of the first batch can still be computed.
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+}
+
+#[test]
+fn it_can_prove_accounts_at_head() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+    let key = KeyNibbles::from(&Address::from(&key_pair.public));
+
+    let head = blockchain.head();
+    let proof = blockchain
+        .get_accounts_proof_at(&head.hash(), vec![&key])
+        .unwrap();
+    assert!(proof.verify(head.state_root()));
+
+    // Proofs can only be produced for the current head.
+    let parent_hash = head.parent_hash().clone();
+    assert!(blockchain
+        .get_accounts_proof_at(&parent_hash, vec![&key])
+        .is_none());
+    assert!(blockchain
+        .get_accounts_proof_at(&Blake2bHash::default(), vec![&key])
+        .is_none());
+}
+
+#[test]
+fn it_counts_consecutive_skip_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let regular_block = temp_producer.next_block(vec![], false);
+    temp_producer.next_block(vec![], true);
+    let skip_block = temp_producer.next_block(vec![], true);
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(
+        blockchain.consecutive_skip_blocks_at(regular_block.block_number()),
+        Some(0)
+    );
+    assert_eq!(
+        blockchain.consecutive_skip_blocks_at(skip_block.block_number() - 1),
+        Some(1)
+    );
+    assert_eq!(
+        blockchain.consecutive_skip_blocks_at(skip_block.block_number()),
+        Some(2)
+    );
+    assert_eq!(
+        blockchain.consecutive_skip_blocks_at(skip_block.block_number() + 1),
+        None
+    );
+}
+
+#[test]
+fn it_can_prove_transactions_by_address() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 3, 1);
+    let recipient = txs[0].recipient.clone();
+    let block = temp_producer.next_block_with_txs(vec![], false, txs);
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+
+    // Only the transaction to the given recipient is proven.
+    let addresses = HashSet::from([recipient.clone()]);
+    let proof = blockchain
+        .get_transactions_proof(&block.hash(), &addresses)
+        .unwrap();
+    assert_eq!(proof.history.len(), 1);
+    assert_eq!(proof.history[0].block_number, block.block_number());
+    assert_eq!(proof.verify(block.history_root().clone()), Some(true));
+
+    // No transaction touches an unrelated address.
+    let addresses = HashSet::from([Address::burn_address()]);
+    let proof = blockchain
+        .get_transactions_proof(&block.hash(), &addresses)
+        .unwrap();
+    assert!(proof.history.is_empty());
+
+    // Unknown blocks can't be proven.
+    assert!(blockchain
+        .get_transactions_proof(&Blake2bHash::default(), &addresses)
+        .is_none());
+}
+
+#[test]
+fn it_returns_block_with_history_proof() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    // Create a fork block.
+    let fork = temp_producer2.next_block(vec![0x42], false);
+
+    let block_number = temp_producer1.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 3, 1);
+    let block = temp_producer1.next_block_with_txs(vec![], false, txs);
+    temp_producer1.next_block(vec![], false);
+    assert_eq!(temp_producer1.push(fork.clone()), Ok(PushResult::Forked));
+
+    // Finish two batches, so that the last macro block distributes rewards.
+    produce_macro_blocks(&temp_producer1.producer, &temp_producer1.blockchain, 2);
+
+    let blockchain = temp_producer1.blockchain.read();
+
+    // All transactions of the micro block are proven against its history root.
+    let (proven_block, proof) = blockchain
+        .get_block_with_history_proof(&block.hash())
+        .unwrap();
+    assert_eq!(proven_block, block);
+    assert_eq!(proof.history.len(), 3);
+    assert_eq!(
+        proof.history,
+        blockchain
+            .history_store
+            .get_block_transactions(block.block_number(), None)
+    );
+    assert_eq!(proof.verify(block.history_root().clone()), Some(true));
+
+    // The reward inherents of the macro block are proven against its history root.
+    let macro_block = blockchain.head();
+    let (proven_block, proof) = blockchain
+        .get_block_with_history_proof(&macro_block.hash())
+        .unwrap();
+    assert_eq!(proven_block, macro_block);
+    assert!(!proof.history.is_empty());
+    assert_eq!(proof.verify(macro_block.history_root().clone()), Some(true));
+
+    // Fork and unknown blocks can't be proven.
+    assert!(blockchain
+        .get_block_with_history_proof(&fork.hash())
+        .is_none());
+    assert!(blockchain
+        .get_block_with_history_proof(&Blake2bHash::default())
+        .is_none());
+}
+
+#[test]
+fn it_reads_head_hash_from_store() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block = temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let txn = blockchain.read_transaction();
+    assert_eq!(blockchain.head_hash_from_store(&txn), Some(block.hash()));
+    assert_eq!(
+        blockchain.head_hash_from_store(&txn),
+        Some(blockchain.head_hash())
+    );
+}
+
+#[test]
+fn it_can_prove_election_blocks_on_main_chain() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        3 * Policy::batches_per_epoch() as usize,
+    );
+
+    // Create a main chain micro block and a fork block at the same height.
+    let fork = temp_producer.next_block_no_push(vec![0x42], false);
+    let micro_block = temp_producer.next_block(vec![], false);
+    assert_eq!(temp_producer.push(fork.clone()), Ok(PushResult::Forked));
+
+    let blockchain = temp_producer.blockchain.read();
+    let election_head = blockchain.election_head();
+
+    let target = blockchain
+        .get_block_at(Policy::election_block_of(1).unwrap(), true, None)
+        .unwrap()
+        .unwrap_macro();
+    let proof = blockchain.main_chain_proof(&target.hash()).unwrap();
+    assert!(proof.is_block_proven(&election_head, &target));
+
+    // Only election blocks can be proven, other main chain blocks are rejected as such.
+    let checkpoint = blockchain
+        .get_block_at(Policy::macro_block_of(1).unwrap(), false, None)
+        .unwrap();
+    assert!(matches!(
+        blockchain.main_chain_proof(&checkpoint.hash()),
+        Err(BlockchainError::BlockIsNotElection)
+    ));
+    assert!(blockchain.is_on_main_chain(&micro_block.hash()));
+    assert!(matches!(
+        blockchain.main_chain_proof(&micro_block.hash()),
+        Err(BlockchainError::BlockIsNotElection)
+    ));
+
+    // Blocks that are not on the main chain or are unknown can't be proven.
+    assert!(matches!(
+        blockchain.main_chain_proof(&fork.hash()),
+        Err(BlockchainError::BlockNotOnMainChain)
+    ));
+    assert!(matches!(
+        blockchain.main_chain_proof(&Blake2bHash::default()),
+        Err(BlockchainError::BlockNotFound)
+    ));
+}
+
+#[test]
+fn it_can_filter_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+    let filtered_tx =
+        generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 1, 0).remove(0);
+    let accepted_tx =
+        generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 1, 1).remove(0);
+
+    let blacklisted = filtered_tx.recipient.clone();
+    temp_producer
+        .blockchain
+        .read()
+        .set_transaction_filter(Box::new(move |tx: &Transaction| {
+            tx.sender != blacklisted && tx.recipient != blacklisted
+        }));
+
+    let block = temp_producer.next_block_no_push_with_txs(vec![], false, vec![filtered_tx]);
+    assert_eq!(
+        temp_producer.push(block.clone()),
+        Err(PushError::InvalidBlock(BlockError::FilteredTransaction))
+    );
+
+    // The filter also applies to trusted pushes.
+    assert_eq!(
+        Blockchain::trusted_push(temp_producer.blockchain.upgradable_read(), block),
+        Err(PushError::InvalidBlock(BlockError::FilteredTransaction))
+    );
+
+    let block = temp_producer.next_block_no_push_with_txs(vec![], false, vec![accepted_tx]);
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+}
+
+#[test]
+fn it_can_filter_transactions_by_sender() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    let block_number = temp_producer.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 2, 0);
+
+    let blacklisted = Address::from(&key_pair.public);
+    temp_producer
+        .blockchain
+        .read()
+        .set_transaction_filter(Box::new(move |tx: &Transaction| tx.sender != blacklisted));
+
+    let head_hash = temp_producer.blockchain.read().head_hash();
+    let block = temp_producer.next_block_no_push_with_txs(vec![], false, txs);
+    assert_eq!(
+        temp_producer.push(block.clone()),
+        Err(PushError::InvalidBlock(BlockError::FilteredTransaction))
+    );
+    assert_eq!(temp_producer.blockchain.read().head_hash(), head_hash);
+
+    // Trusted pushes, as done by validators for their own blocks, are filtered as well.
+    assert_eq!(
+        Blockchain::trusted_push(temp_producer.blockchain.upgradable_read(), block),
+        Err(PushError::InvalidBlock(BlockError::FilteredTransaction))
+    );
+    assert_eq!(temp_producer.blockchain.read().head_hash(), head_hash);
+
+    // Blocks without transactions from the sender are still accepted.
+    let block = temp_producer.next_block_no_push(vec![], false);
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+}
+
+#[test]
+fn it_rejects_replayed_transactions_early() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    let block_number = temp_producer1.blockchain.read().block_number() + 1;
+    let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 1, 0);
+    temp_producer1.next_block_with_txs(vec![], false, txs.clone());
+
+    // A block on top of the head that replays the transaction is rejected before it is verified,
+    // so even an invalid justification isn't reported.
+    let mut block = temp_producer1.next_block_no_push_with_txs(vec![], false, txs.clone());
+    if let Block::Micro(ref mut micro_block) = block {
+        micro_block.header.extra_data = vec![0x42];
+    }
+    assert_eq!(
+        temp_producer1.push(block),
+        Err(PushError::DuplicateTransaction)
+    );
+
+    // A fork block that includes the same transaction is not affected by the early check.
+    temp_producer1.next_block(vec![], false);
+    let fork = temp_producer2.next_block_with_txs(vec![0x42], false, txs);
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Forked));
+}
+
+#[test]
+fn it_lists_micro_blocks_since_macro() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    assert!(temp_producer
+        .blockchain
+        .read()
+        .micro_blocks_since_macro()
+        .is_empty());
+
+    // Produce a batch and a few micro blocks on top of the macro block.
+    for _ in 0..Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+    let mut blocks = vec![];
+    blocks.push(temp_producer.next_block(vec![], false));
+    blocks.push(temp_producer.next_block(vec![], true));
+    blocks.push(temp_producer.next_block(vec![], false));
+
+    let blockchain = temp_producer.blockchain.read();
+    let hashes = blockchain.micro_blocks_since_macro();
+    assert_eq!(
+        hashes.len() as u32,
+        blockchain.block_number() - blockchain.macro_head().block_number()
+    );
+    assert_eq!(
+        hashes,
+        blocks.iter().map(|block| block.hash()).collect::<Vec<_>>()
+    );
+
+    // The hashes are contiguous.
+    for (i, hash) in hashes.iter().enumerate().skip(1) {
+        let block = blockchain.get_block(hash, false, None).unwrap();
+        assert_eq!(block.parent_hash(), &hashes[i - 1]);
+    }
+}
+
+#[test]
+fn it_can_iterate_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let genesis_hash = temp_producer.blockchain.read().head_hash();
+
+    for _ in 0..Policy::blocks_per_batch() + 3 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let head_hash = blockchain.head_hash();
+    let count = Policy::blocks_per_batch() + 3;
+
+    // Forward iteration matches `get_blocks`.
+    let blocks = blockchain
+        .get_blocks(&genesis_hash, count, true, Direction::Forward, None)
+        .unwrap();
+    let iterated: Vec<_> = blockchain
+        .blocks_iter(&genesis_hash, true, Direction::Forward)
+        .collect();
+    assert_eq!(iterated.len(), count as usize);
+    assert_eq!(iterated, blocks);
+
+    // Backward iteration matches `get_blocks` and stops at the genesis block.
+    let blocks = blockchain
+        .get_blocks(&head_hash, count, false, Direction::Backward, None)
+        .unwrap();
+    let iterated: Vec<_> = blockchain
+        .blocks_iter(&head_hash, false, Direction::Backward)
+        .collect();
+    assert_eq!(iterated.len(), count as usize);
+    assert_eq!(iterated, blocks);
+
+    // Iteration can stop early.
+    let iterated: Vec<_> = blockchain
+        .blocks_iter(&genesis_hash, false, Direction::Forward)
+        .take(2)
+        .collect();
+    assert_eq!(iterated.len(), 2);
+
+    // There are no blocks beyond the head or for unknown hashes.
+    assert_eq!(
+        blockchain
+            .blocks_iter(&head_hash, false, Direction::Forward)
+            .count(),
+        0
+    );
+    assert_eq!(
+        blockchain
+            .blocks_iter(&Blake2bHash::default(), false, Direction::Backward)
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn it_notifies_about_ignored_blocks() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0]
+    //    \- [1]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    let inferior1 = temp_producer1.next_block(vec![], false);
+    temp_producer2.next_block(vec![], true);
+    let inferior2 = temp_producer1.next_block(vec![], false);
+
+    let mut rx = temp_producer2.blockchain.read().notifier.subscribe();
+
+    assert_eq!(
+        temp_producer2.push(inferior1.clone()),
+        Ok(PushResult::Ignored)
+    );
+    assert!(matches!(rx.try_recv(), Ok(BlockchainEvent::Stored(_))));
+    assert_eq!(
+        rx.try_recv(),
+        Ok(BlockchainEvent::Ignored(
+            inferior1.hash(),
+            inferior1.block_number()
+        ))
+    );
+
+    assert_eq!(
+        temp_producer2.push(inferior2.clone()),
+        Ok(PushResult::Ignored)
+    );
+    assert!(matches!(rx.try_recv(), Ok(BlockchainEvent::Stored(_))));
+    assert_eq!(
+        rx.try_recv(),
+        Ok(BlockchainEvent::Ignored(
+            inferior2.hash(),
+            inferior2.block_number()
+        ))
+    );
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn it_can_get_slot_owners() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..3 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let block_number = blockchain.block_number() + 1;
+    let slots = blockchain.get_slot_owners_at(block_number, None).unwrap();
+    assert_eq!(slots.len(), Policy::SLOTS as usize);
+
+    // The single slot lookups are consistent with the full assignment.
+    for offset in 0..5 {
+        let proposer = blockchain
+            .get_proposer_at(block_number, block_number + offset, None)
+            .unwrap();
+        let slot = &slots[proposer.number as usize];
+        assert_eq!(slot.number, proposer.number);
+        assert_eq!(slot.band, proposer.band);
+        assert_eq!(slot.validator.address, proposer.validator.address);
+    }
+
+    // The validators of future epochs are unknown.
+    assert!(blockchain
+        .get_slot_owners_at(Policy::blocks_per_epoch() * 2 + 1, None)
+        .is_none());
+}
+
+#[test]
+fn it_finds_active_producers_in_epoch() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let mut blocks = vec![];
+    for skip_block in [false, false, true, false] {
+        blocks.push(temp_producer.next_block(vec![], skip_block));
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let active_slots = blockchain.active_producers_in_epoch(1).unwrap();
+
+    let mut produced_slots = HashSet::new();
+    for block in blocks.iter().filter(|block| !block.is_skip()) {
+        let proposer = blockchain.get_proposer_of(&block.hash(), None).unwrap();
+        produced_slots.insert(proposer.number);
+    }
+    assert_eq!(active_slots, produced_slots);
+
+    // The slot that was skipped did not produce anything.
+    let skipped_slot = blockchain
+        .get_proposer_of(&blocks[2].hash(), None)
+        .unwrap()
+        .number;
+    assert!(!active_slots.contains(&skipped_slot));
+
+    // The next epoch has not started yet.
+    assert_eq!(blockchain.active_producers_in_epoch(2), None);
+}
+
+#[test]
+fn it_computes_next_validators_deterministically() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let staking_contract = blockchain.get_staking_contract();
+    let seed = blockchain.head().seed().clone();
+    let data_store = blockchain.get_staking_contract_store();
+    let txn = blockchain.read_transaction();
+    let data_store_read = data_store.read(&txn);
+
+    let validators =
+        Blockchain::compute_next_validators(&staking_contract, &data_store_read, &seed);
+    assert_eq!(validators.num_validators(), 1);
+    assert_eq!(validators.validators[0].address, validator_address());
+    assert_eq!(
+        validators,
+        Blockchain::compute_next_validators(&staking_contract, &data_store_read, &seed)
+    );
+    assert_eq!(Ok(validators), blockchain.next_validators(&seed));
+}
+
+#[test]
+fn it_can_fetch_recent_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..3 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let head_hash = blockchain.head_hash();
+
+    let blocks = blockchain.recent_blocks(2, true);
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].hash(), head_hash);
+    assert_eq!(&blocks[1].hash(), blocks[0].parent_hash());
+    assert!(blocks[0].body().is_some());
+
+    // Near genesis, fewer blocks are returned.
+    let blocks = blockchain.recent_blocks(20, false);
+    assert_eq!(blocks.len(), 4);
+    assert_eq!(blocks[0].hash(), head_hash);
+    assert_eq!(blocks[3].block_number(), Policy::genesis_block_number());
+
+    assert!(blockchain.recent_blocks(0, false).is_empty());
+}
+
+#[test]
+fn it_can_get_blocks_in_height_range() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..5 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let genesis_block_number = Policy::genesis_block_number();
+    let head_block_number = blockchain.block_number();
+
+    // A normal range includes both bounds.
+    let blocks = blockchain.get_blocks_in_height_range(
+        genesis_block_number + 1,
+        genesis_block_number + 3,
+        true,
+    );
+    let block_numbers: Vec<_> = blocks.iter().map(|block| block.block_number()).collect();
+    assert_eq!(
+        block_numbers,
+        vec![
+            genesis_block_number + 1,
+            genesis_block_number + 2,
+            genesis_block_number + 3
+        ]
+    );
+    assert!(blocks.iter().all(|block| block.body().is_some()));
+
+    // The upper bound is clamped to the head.
+    let blocks =
+        blockchain.get_blocks_in_height_range(head_block_number - 1, head_block_number + 10, false);
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[1].hash(), blockchain.head_hash());
+
+    // Inverted bounds yield no blocks.
+    assert!(blockchain
+        .get_blocks_in_height_range(genesis_block_number + 3, genesis_block_number + 1, false)
+        .is_empty());
+    assert!(blockchain
+        .get_blocks_in_height_range(head_block_number + 1, head_block_number + 10, false)
+        .is_empty());
+}
+
+#[test(tokio::test)]
+async fn it_replays_events_since_a_block() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Produce blocks up to and including the first macro block, plus one micro block.
+    let mut blocks = vec![];
+    for _ in 0..Policy::blocks_per_batch() + 1 {
+        blocks.push(temp_producer.next_block(vec![], false));
+    }
+    let since = blocks[blocks.len() - 4].hash();
+    let macro_block = &blocks[blocks.len() - 2];
+    assert!(macro_block.is_macro());
+
+    let mut events = temp_producer
+        .blockchain
+        .read()
+        .notifier_as_stream_from(&since);
+
+    // The events of the blocks after `since` are replayed in push order.
+    let mut expected_events = vec![
+        BlockchainEvent::Extended(blocks[blocks.len() - 3].hash()),
+        BlockchainEvent::Extended(macro_block.hash()),
+    ];
+    if macro_block.is_election() {
+        expected_events.push(BlockchainEvent::EpochFinalized(macro_block.hash()));
+    } else {
+        expected_events.push(BlockchainEvent::Finalized(macro_block.hash()));
+    }
+    expected_events.push(BlockchainEvent::Extended(blocks[blocks.len() - 1].hash()));
+    for expected_event in expected_events {
+        assert_eq!(events.next().await, Some(expected_event));
+    }
+
+    // Afterwards, live events are delivered.
+    let block = temp_producer.next_block(vec![], false);
+    assert_eq!(
+        events.next().await,
+        Some(BlockchainEvent::Extended(block.hash()))
+    );
+
+    // An unknown block results in a resync to the current head.
+    let blockchain = temp_producer.blockchain.read();
+    let mut events = blockchain.notifier_as_stream_from(&Blake2bHash::default());
+    assert_eq!(
+        events.next().await,
+        Some(BlockchainEvent::Rebranched(
+            vec![],
+            vec![(blockchain.head_hash(), blockchain.head())]
+        ))
+    );
+    drop(blockchain);
+
+    // So does a block that is further behind the head than the replay limit.
+    temp_producer.blockchain.write().config.max_replayed_blocks = 3;
+    let blockchain = temp_producer.blockchain.read();
+    let mut events = blockchain.notifier_as_stream_from(&since);
+    assert_eq!(
+        events.next().await,
+        Some(BlockchainEvent::Rebranched(
+            vec![],
+            vec![(blockchain.head_hash(), blockchain.head())]
+        ))
+    );
+    let mut events = blockchain.notifier_as_stream_from(&blocks[blocks.len() - 1].hash());
+    assert_eq!(
+        events.next().await,
+        Some(BlockchainEvent::Extended(block.hash()))
+    );
+}
+
+#[test]
+fn it_detects_genesis_accounts_mismatch_on_load() {
+    let env = VolatileDatabase::new(20).unwrap();
+    let time = Arc::new(OffsetTime::new());
+    let network_info = NetworkInfo::from_network_id(NetworkId::UnitAlbatross);
+
+    // Initialize the database with the network's genesis.
+    Blockchain::new(
+        env.clone(),
+        BlockchainConfig::default(),
+        NetworkId::UnitAlbatross,
+        Arc::clone(&time),
+    )
+    .unwrap();
+
+    // Loading it again with the same genesis works.
+    assert!(Blockchain::new(
+        env.clone(),
+        BlockchainConfig::default(),
+        NetworkId::UnitAlbatross,
+        Arc::clone(&time),
+    )
+    .is_ok());
+
+    // Loading it with genesis accounts that don't match the genesis block fails.
+    let mut genesis_accounts = network_info.genesis_accounts();
+    assert!(genesis_accounts.pop().is_some());
+    let result = Blockchain::with_genesis(
+        env,
+        BlockchainConfig::default(),
+        time,
+        NetworkId::UnitAlbatross,
+        network_info.genesis_block(),
+        genesis_accounts,
+    );
+    assert!(matches!(
+        result,
+        Err(BlockchainError::GenesisAccountsMismatch)
+    ));
+}
+
+#[test]
+fn it_skips_genesis_accounts_check_on_load_past_genesis() {
+    let env = VolatileDatabase::new(20).unwrap();
+    let time = Arc::new(OffsetTime::new());
+    let network_info = NetworkInfo::from_network_id(NetworkId::UnitAlbatross);
+
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(
+            env.clone(),
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::clone(&time),
+        )
+        .unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks(&producer, &blockchain, 1);
+    let head_hash = blockchain.read().head_hash();
+    drop(blockchain);
+
+    // Once the chain has moved past genesis and its state is consistent, the genesis accounts
+    // are not rebuilt on load, so a mismatch goes unnoticed.
+    let mut genesis_accounts = network_info.genesis_accounts();
+    assert!(genesis_accounts.pop().is_some());
+    let blockchain = Blockchain::with_genesis(
+        env,
+        BlockchainConfig::default(),
+        time,
+        NetworkId::UnitAlbatross,
+        network_info.genesis_block(),
+        genesis_accounts,
+    )
+    .unwrap();
+    assert_eq!(blockchain.head_hash(), head_hash);
+}
+
+#[test]
+fn it_caches_validators_of_past_epochs() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize * 3,
+    );
+
+    let blockchain = temp_producer.blockchain.read();
+    let epoch = 1;
+    assert!(epoch + 1 < Policy::epoch_at(blockchain.block_number()));
+
+    // The first lookup reads the election block from the store and caches its validators.
+    let validators = blockchain.get_validators_for_epoch(epoch, None).unwrap();
+
+    // Remove all blocks from the store. Any further lookup that reads from the store now fails.
+    let mut txn = blockchain.write_transaction();
+    blockchain.chain_store.clear(&mut txn);
+    txn.commit();
+
+    // Repeated lookups for the same epoch are served from the cache.
+    for _ in 0..100 {
+        assert_eq!(
+            blockchain.get_validators_for_epoch(epoch, None),
+            Ok(validators.clone())
+        );
+    }
+}
+
+#[test]
+fn it_shares_validators_of_current_and_previous_epoch() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize * 3,
+    );
+
+    let blockchain = temp_producer.blockchain.read();
+    let current_epoch = Policy::epoch_at(blockchain.block_number());
+
+    // The current and previous validators are handed out without copying them.
+    let current = blockchain
+        .get_shared_validators_for_epoch(current_epoch, None)
+        .unwrap();
+    assert!(Arc::ptr_eq(
+        &current,
+        blockchain.state.current_slots.as_ref().unwrap()
+    ));
+    let previous = blockchain
+        .get_shared_validators_for_epoch(current_epoch - 1, None)
+        .unwrap();
+    assert!(Arc::ptr_eq(
+        &previous,
+        blockchain.state.previous_slots.as_ref().unwrap()
+    ));
+
+    // Both accessors return the same validators for every epoch.
+    for epoch in 1..=current_epoch {
+        assert_eq!(
+            *blockchain
+                .get_shared_validators_for_epoch(epoch, None)
+                .unwrap(),
+            blockchain.get_validators_for_epoch(epoch, None).unwrap()
+        );
+    }
+}
+
+#[test]
+fn it_returns_next_block_parents() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize + 1,
+    );
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let (parent_hash, parent_election_hash) = blockchain.next_block_parents();
+    assert_eq!(parent_hash, blockchain.head_hash());
+    assert_eq!(parent_election_hash, blockchain.election_head_hash());
+    assert_ne!(parent_hash, parent_election_hash);
+}
+
+#[test]
+fn it_returns_head_info() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize + 1,
+    );
+    temp_producer.next_block(vec![], false);
+    temp_producer.next_block(vec![], true);
+
+    let blockchain = temp_producer.blockchain.read();
+    let head = blockchain.head();
+    let head_info = blockchain.head_info();
+    assert_eq!(head_info.hash, blockchain.head_hash());
+    assert_eq!(head_info.block_number, blockchain.block_number());
+    assert!(head_info.is_skip);
+    assert_eq!(head_info.timestamp, head.timestamp());
+    assert_eq!(head_info.macro_head_hash, blockchain.macro_head_hash());
+    assert_eq!(
+        head_info.election_head_hash,
+        blockchain.election_head_hash()
+    );
+    assert_ne!(head_info.macro_head_hash, head_info.election_head_hash);
+}
+
+#[test]
+fn it_estimates_state_memory() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let initial_estimate = temp_producer.blockchain.read().state_memory_estimate();
+    assert!(initial_estimate > 0);
+
+    // At genesis, the previous slots are empty. After the first election block they contain the
+    // genesis validators.
+    assert_eq!(
+        temp_producer
+            .blockchain
+            .read()
+            .previous_validators()
+            .unwrap()
+            .num_validators(),
+        0
+    );
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize,
+    );
+
+    let blockchain = temp_producer.blockchain.read();
+    assert!(blockchain.previous_validators().unwrap().num_validators() > 0);
+    assert!(blockchain.state_memory_estimate() > initial_estimate);
+}
+
+#[test]
+fn it_can_prune_block_bodies() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize,
+    );
+    for _ in 0..3 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let election_block_number = blockchain.election_head().block_number();
+
+    // Only the micro blocks of the previous epoch are pruned, even if a later block number is
+    // requested.
+    assert_eq!(
+        blockchain.prune_bodies_before(u32::MAX),
+        (Policy::blocks_per_epoch() - Policy::batches_per_epoch()) as usize
+    );
+    assert_eq!(blockchain.prune_bodies_before(u32::MAX), 0);
+
+    // Later calls start from the height up to which bodies were already pruned.
+    assert_eq!(
+        blockchain.chain_store.get_pruned_bodies_height(None),
+        Some(election_block_number)
+    );
+
+    for height in 1..=blockchain.block_number() {
+        let block = blockchain.get_block_at(height, true, None).unwrap();
+        match block {
+            Block::Macro(macro_block) => assert!(macro_block.body.is_some()),
+            Block::Micro(micro_block) => {
+                assert_eq!(micro_block.body.is_none(), height < election_block_number);
+            }
+        }
+    }
+
+    // Pruned blocks can still be navigated without their bodies.
+    let first_hash = blockchain.get_block_at(1, false, None).unwrap().hash();
+    assert!(blockchain.get_block(&first_hash, true, None).is_err());
+    let blocks = blockchain
+        .get_blocks(
+            &blockchain.head_hash(),
+            blockchain.block_number() - 1,
+            false,
+            Direction::Backward,
+            None,
+        )
+        .unwrap();
+    assert_eq!(blocks.last().unwrap().hash(), first_hash);
+
+    // Pruned blocks can't be requested with their bodies.
+    assert_eq!(
+        blockchain.get_blocks(
+            &blockchain.head_hash(),
+            blockchain.block_number() - 1,
+            true,
+            Direction::Backward,
+            None,
+        ),
+        Err(BlockchainError::BlockNotFound)
+    );
+    let genesis_hash = blockchain
+        .get_block_at(Policy::genesis_block_number(), false, None)
+        .unwrap()
+        .hash();
+    assert_eq!(
+        blockchain.get_blocks(&genesis_hash, 2, true, Direction::Forward, None),
+        Err(BlockchainError::BlockNotFound)
+    );
+}
+
+#[test]
+fn it_can_get_block_hash_at() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    for height in 0..=blockchain.block_number() {
+        assert_eq!(
+            blockchain.get_block_hash_at(height),
+            blockchain
+                .get_block_at(height, false, None)
+                .ok()
+                .map(|block| block.hash())
+        );
+    }
+    assert_eq!(
+        blockchain.get_block_hash_at(blockchain.block_number()),
+        Some(blockchain.head_hash())
+    );
+    assert_eq!(
+        blockchain.get_block_hash_at(blockchain.block_number() + 1),
+        None
+    );
+}
+
+#[test]
+fn it_estimates_next_macro_timestamp() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+
+    // Right after a macro block, the policy's block separation time is assumed.
+    {
+        let blockchain = temp_producer.blockchain.read();
+        assert_eq!(
+            blockchain.estimated_next_macro_timestamp(),
+            blockchain.head().timestamp()
+                + u64::from(Policy::blocks_per_batch()) * Policy::BLOCK_SEPARATION_TIME
+        );
+    }
+
+    // Produce half a batch with regular block spacing.
+    for _ in 0..Policy::blocks_per_batch() / 2 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let head = blockchain.head();
+    let macro_block_number = Policy::macro_block_after(head.block_number());
+    let remaining_blocks = u64::from(macro_block_number - head.block_number());
+
+    let estimate = blockchain.estimated_next_macro_timestamp();
+    assert!(estimate > head.timestamp());
+    assert!(estimate <= head.timestamp() + remaining_blocks * Policy::BLOCK_SEPARATION_TIME);
+    assert!(estimate >= head.timestamp() + remaining_blocks * (Policy::BLOCK_SEPARATION_TIME - 1));
+}
+
+#[test]
+fn it_counts_confirmations() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let buried_block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(buried_block.clone()).unwrap();
+
+    // Producer 2 prefers its skip block over the micro block of producer 1.
+    let fork_block = temp_producer1.next_block(vec![], false);
+    temp_producer2.next_block(vec![], true);
+    assert_eq!(
+        temp_producer2.push(fork_block.clone()),
+        Ok(PushResult::Ignored)
+    );
+    temp_producer2.next_block(vec![], false);
+
+    let blockchain = temp_producer2.blockchain.read();
+
+    // The head.
+    let head_hash = blockchain.head_hash();
+    assert!(blockchain.is_on_main_chain(&head_hash));
+    assert_eq!(blockchain.confirmations(&head_hash), Some(1));
+
+    // A buried block.
+    assert!(blockchain.is_on_main_chain(&buried_block.hash()));
+    assert_eq!(blockchain.confirmations(&buried_block.hash()), Some(3));
+
+    // A fork block.
+    assert!(!blockchain.is_on_main_chain(&fork_block.hash()));
+    assert_eq!(blockchain.confirmations(&fork_block.hash()), None);
+
+    // An unknown block.
+    assert!(!blockchain.is_on_main_chain(&Blake2bHash::default()));
+    assert_eq!(blockchain.confirmations(&Blake2bHash::default()), None);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn it_can_serialize_slots_as_json() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let blockchain = temp_producer.blockchain.read();
+
+    let slots: Vec<nimiq_blockchain::ValidatorSlotsJson> =
+        serde_json::from_str(&blockchain.slots_as_json()).unwrap();
+    let validators = blockchain.current_validators().unwrap();
+    assert_eq!(slots.len(), validators.num_validators());
+
+    let total_slots: u16 = slots.iter().map(|slot| slot.num_slots).sum();
+    assert_eq!(total_slots, Policy::SLOTS);
+    assert_eq!(slots[0].first_slot, 0);
+    let address = validator_address().to_user_friendly_address();
+    assert!(slots.iter().any(|slot| slot.address == address));
+    assert!(slots.iter().all(|slot| slot.reward_address.is_some()));
+}
+
+#[test]
+fn it_verifies_state_consistency() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..Policy::blocks_per_batch() + 1 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(blockchain.verify_state_consistency(), Ok(()));
+
+    // Corrupt the accounts tree by changing an account behind the blockchain's back.
+    let accounts = &blockchain.state.accounts;
+    let mut txn = accounts.env.write_transaction();
+    accounts
+        .tree
+        .put(
+            &mut (&mut txn).into(),
+            &KeyNibbles::from(&validator_address()),
+            Account::Basic(BasicAccount {
+                balance: Coin::from_u64_unchecked(1),
+            }),
+        )
+        .unwrap();
+    accounts.tree.update_root(&mut (&mut txn).into()).unwrap();
+    txn.commit();
+
+    assert_eq!(
+        blockchain.verify_state_consistency(),
+        Err(BlockchainError::InconsistentState)
+    );
+}
+
+#[test]
+fn it_finds_macro_block_for_height() {
+    let temp_producer = TemporaryBlockProducer::new();
+    for _ in 0..Policy::blocks_per_batch() + 1 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let genesis_block_number = Policy::genesis_block_number();
+    let first_macro_block_number = Policy::macro_block_of(1).unwrap();
+
+    // The genesis block finalizes itself.
+    let macro_block = blockchain
+        .macro_block_for_height(genesis_block_number, false)
+        .unwrap();
+    assert_eq!(macro_block.block_number(), genesis_block_number);
+
+    // The first and last block of a batch are finalized by the macro block of that batch.
+    for block_number in [genesis_block_number + 1, first_macro_block_number] {
+        let macro_block = blockchain
+            .macro_block_for_height(block_number, true)
+            .unwrap();
+        assert_eq!(macro_block.block_number(), first_macro_block_number);
+        assert!(macro_block.body.is_some());
+    }
+    let macro_block = blockchain
+        .macro_block_for_height(first_macro_block_number, false)
+        .unwrap();
+    assert!(macro_block.body.is_none());
+
+    // The current batch and future batches are not finalized yet.
+    assert!(blockchain
+        .macro_block_for_height(first_macro_block_number + 1, false)
+        .is_none());
+    assert!(blockchain
+        .macro_block_for_height(Policy::macro_block_of(5).unwrap(), false)
+        .is_none());
+}
+
+#[test]
+fn it_can_start_from_custom_genesis() {
+    let address =
+        Address::from_user_friendly_address("NQ39 VBTN P2HX Q3MF KHF1 CCLA G6FS 9B8S VY28")
+            .unwrap();
+    let mut genesis_builder = GenesisBuilder::default();
+    genesis_builder
+        .with_network(NetworkId::UnitAlbatross)
+        .with_genesis_block_number(Policy::genesis_block_number())
+        .with_genesis_validator(
+            validator_address(),
+            signing_key().public,
+            voting_key().public_key,
+            validator_address(),
+            None,
+            None,
+            false,
+        )
+        .with_basic_account(address.clone(), Coin::from_u64_unchecked(42));
+    let genesis_info = genesis_builder
+        .generate(VolatileDatabase::new(20).unwrap())
+        .unwrap();
+
+    // Genesis accounts that don't match the genesis block are rejected.
+    let mut genesis_accounts = genesis_info.accounts.clone();
+    assert!(genesis_accounts.pop().is_some());
+    let result = Blockchain::new_with_genesis(
+        VolatileDatabase::new(20).unwrap(),
+        NetworkId::UnitAlbatross,
+        genesis_info.block.clone(),
+        genesis_accounts,
+    );
+    assert!(matches!(
+        result,
+        Err(BlockchainError::GenesisAccountsMismatch)
+    ));
+
+    let blockchain = Blockchain::new_with_genesis(
+        VolatileDatabase::new(20).unwrap(),
+        NetworkId::UnitAlbatross,
+        genesis_info.block,
+        genesis_info.accounts,
+    )
+    .unwrap();
+
+    assert_eq!(blockchain.head_hash(), genesis_info.hash);
+    assert_eq!(blockchain.block_number(), Policy::genesis_block_number());
+    assert_eq!(
+        blockchain
+            .get_account_if_complete(&address)
+            .unwrap()
+            .balance(),
+        Coin::from_u64_unchecked(42)
+    );
+    assert_eq!(blockchain.verify_state_consistency(), Ok(()));
+}
+
+#[test]
+fn it_fails_to_select_validators_without_staking_contract() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let seed = blockchain.head().seed().clone();
+    assert!(blockchain.next_validators(&seed).is_ok());
+
+    // Replace the staking contract with a basic account.
+    let accounts = &blockchain.state.accounts;
+    let mut txn = accounts.env.write_transaction();
+    accounts
+        .tree
+        .put(
+            &mut (&mut txn).into(),
+            &KeyNibbles::from(&Policy::STAKING_CONTRACT_ADDRESS),
+            Account::Basic(BasicAccount {
+                balance: Coin::from_u64_unchecked(1),
+            }),
+        )
+        .unwrap();
+    accounts.tree.update_root(&mut (&mut txn).into()).unwrap();
+    txn.commit();
+
+    assert_eq!(
+        blockchain.next_validators(&seed),
+        Err(BlockchainError::MissingStakingContract)
+    );
+}
+
+#[test]
+fn it_checks_if_block_exists_at_height() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize + 1,
+    );
+    temp_producer.next_block(vec![], false);
+
+    // Prune the first epoch to create gaps.
+    {
+        let blockchain = temp_producer.blockchain.read();
+        let mut txn = blockchain.write_transaction();
+        blockchain.chain_store.prune_epoch(1, &mut txn);
+        txn.commit();
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert!(!blockchain.contains_block_at(Policy::genesis_block_number() + 1));
+    assert!(blockchain.contains_block_at(blockchain.block_number()));
+    assert!(!blockchain.contains_block_at(blockchain.block_number() + 1));
+
+    for height in Policy::genesis_block_number()..=blockchain.block_number() + 3 {
+        assert_eq!(
+            blockchain.contains_block_at(height),
+            blockchain.get_block_at(height, false, None).is_ok(),
+            "height {height}"
+        );
+    }
+}
+
+#[test]
+fn it_returns_next_block_producer() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let block_number = blockchain.block_number() + 1;
+
+    for offset in [block_number, block_number + 1] {
+        let (slot, voting_key) = blockchain.next_block_producer(offset).unwrap();
+
+        let expected = blockchain
+            .get_proposer_at(block_number, offset, None)
+            .unwrap();
+        assert_eq!(slot.number, expected.number);
+        assert_eq!(slot.band, expected.band);
+        assert_eq!(slot.validator, expected.validator);
+        assert_eq!(
+            voting_key,
+            *expected.validator.voting_key.uncompress_unchecked()
+        );
+    }
+}
+
+#[test]
+fn it_reads_accounts_with_a_given_reader() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let txn = blockchain.read_transaction();
+    let store = blockchain.state.accounts.root_data_store();
+    let reader = store.read(&txn);
+
+    // Existing accounts are the same as those read via the live accounts.
+    let account = blockchain
+        .get_account_if_complete(&Policy::STAKING_CONTRACT_ADDRESS)
+        .unwrap();
+    assert!(matches!(account, Account::Staking(_)));
+    assert_eq!(
+        blockchain.get_account_with(&Policy::STAKING_CONTRACT_ADDRESS, &reader),
+        account
+    );
+
+    // Unknown accounts are empty.
+    let address = Address::from([0xabu8; 20]);
+    assert_eq!(
+        blockchain.get_account_with(&address, &reader),
+        blockchain.get_account_if_complete(&address).unwrap()
+    );
+    assert_eq!(
+        blockchain.get_account_with(&address, &reader),
+        Account::default()
+    );
+}
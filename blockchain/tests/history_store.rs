@@ -7,7 +7,7 @@ use nimiq_bls::AggregateSignature;
 use nimiq_database::traits::WriteTransaction;
 use nimiq_genesis::NetworkId;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput};
-use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_primitives::{policy::Policy, TendermintIdentifier, TendermintStep, TendermintVote};
 use nimiq_serde::Deserialize;
 use nimiq_test_log::test;
@@ -476,3 +476,105 @@ fn it_can_find_txns() {
         );
     }
 }
+
+#[test]
+fn it_can_get_transaction_receipts_by_address() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = key_pair_with_funds();
+    let sender = Address::from(&key_pair.public);
+
+    // Push a few blocks with transactions from the same sender.
+    let mut txns = vec![];
+    for i in 0..3 {
+        let block_number = temp_producer.blockchain.read().block_number() + 1;
+        let block_txns =
+            generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 2, i);
+        temp_producer.next_block_with_txs(vec![], false, block_txns.clone());
+        txns.extend(block_txns);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+
+    // The sender limit is respected and receipts are returned newest first.
+    let receipts = blockchain.get_transaction_receipts_by_address(&sender, 3, 10);
+    assert_eq!(receipts.len(), 3);
+    assert!(receipts.windows(2).all(|w| w[0].1 >= w[1].1));
+    assert_eq!(receipts[0].1, blockchain.block_number());
+    for (hash, _) in &receipts {
+        assert!(txns.iter().any(|txn| &txn.hash::<Blake2bHash>() == hash));
+    }
+
+    // The sender never received a basic transaction.
+    let receipts = blockchain.get_transaction_receipts_by_address(&sender, 0, 10);
+    assert!(receipts.is_empty());
+
+    // Recipients only match as recipients.
+    let txn = &txns[0];
+    let receipts = blockchain.get_transaction_receipts_by_address(&txn.recipient, 10, 10);
+    assert_eq!(
+        receipts,
+        vec![(
+            txn.hash::<Blake2bHash>(),
+            Policy::genesis_block_number() + 1
+        )]
+    );
+    let receipts = blockchain.get_transaction_receipts_by_address(&txn.recipient, 10, 0);
+    assert!(receipts.is_empty());
+}
+
+#[test]
+fn it_can_page_through_epoch_transactions() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Add blocks with transactions and a macro block with rewards to the first epoch.
+    let key_pair = key_pair_with_funds();
+    for seed in 0..2 {
+        let mut txns = generate_transactions(
+            &key_pair,
+            temp_producer.blockchain.read().block_number(),
+            NetworkId::UnitAlbatross,
+            3,
+            seed,
+        );
+        txns.sort_unstable();
+        temp_producer.next_block_with_txs(vec![], false, txns);
+    }
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+
+    let all_txs = get_hist_tx(&temp_producer);
+    assert!(all_txs.len() > 6);
+
+    let blockchain = temp_producer.blockchain.read();
+
+    // Reading all pages yields the full list in the same order.
+    for limit in [1, 2, 4, all_txs.len()] {
+        let mut paged_txs = vec![];
+        let mut offset = 0;
+        loop {
+            let page = blockchain
+                .epoch_transactions_paged(1, offset, limit)
+                .unwrap();
+            assert!(page.len() <= limit);
+            if page.is_empty() {
+                break;
+            }
+            offset += page.len();
+            paged_txs.extend(page);
+        }
+        assert_eq!(paged_txs, all_txs);
+    }
+
+    // Pages in the middle and beyond the end.
+    assert_eq!(
+        blockchain.epoch_transactions_paged(1, 2, 3),
+        Some(all_txs[2..5].to_vec())
+    );
+    assert_eq!(
+        blockchain.epoch_transactions_paged(1, all_txs.len(), 10),
+        Some(vec![])
+    );
+    assert_eq!(blockchain.epoch_transactions_paged(1, 0, 0), Some(vec![]));
+
+    // Epochs that are not in the history store.
+    assert_eq!(blockchain.epoch_transactions_paged(2, 0, 10), None);
+}
@@ -1,7 +1,8 @@
 use std::sync::Arc;
 
+use nimiq_block::BlockError;
 use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
-use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
+use nimiq_blockchain_interface::{AbstractBlockchain, PushError, PushResult};
 use nimiq_database::volatile::VolatileDatabase;
 use nimiq_genesis::NetworkId;
 use nimiq_primitives::policy::Policy;
@@ -10,6 +11,7 @@ use nimiq_test_utils::blockchain::{
     fill_micro_blocks_with_txns, produce_macro_blocks, produce_macro_blocks_with_txns, signing_key,
     voting_key,
 };
+use nimiq_transaction::historic_transaction::HistoricTransactionData;
 use nimiq_utils::time::OffsetTime;
 use parking_lot::RwLock;
 
@@ -439,3 +441,85 @@ fn history_sync_works_with_diverging_history() {
 
     assert_eq!(blockchain.head(), blockchain2.read().head());
 }
+
+// Tests that the history sync rejects a macro block if the given history doesn't match the one
+// committed to by the block, and that the rejected history leaves no trace in the chain state.
+#[test]
+fn history_sync_rejects_mismatched_history() {
+    let genesis_block_number = Policy::genesis_block_number();
+    let env = VolatileDatabase::new(20).unwrap();
+    let time = Arc::new(OffsetTime::new());
+    let blockchain1 = Arc::new(RwLock::new(
+        Blockchain::new(
+            env,
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            time,
+        )
+        .unwrap(),
+    ));
+
+    let num_macro_blocks = Policy::batches_per_epoch() as usize;
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks_with_txns(&producer, &blockchain1, num_macro_blocks, 2, 0);
+
+    let env = VolatileDatabase::new(20).unwrap();
+    let time = Arc::new(OffsetTime::new());
+    let blockchain2 = Arc::new(RwLock::new(
+        Blockchain::new(
+            env,
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            time,
+        )
+        .unwrap(),
+    ));
+    let genesis_head = blockchain2.read().head();
+    let genesis_state_root = blockchain2.read().state.accounts.get_root_hash_assert(None);
+
+    let blockchain = blockchain1.read();
+    let election_block_1 = blockchain
+        .chain_store
+        .get_block_at(
+            Policy::blocks_per_epoch() + genesis_block_number,
+            true,
+            None,
+        )
+        .unwrap();
+    let election_txs_1 = blockchain.history_store.get_epoch_transactions(1, None);
+
+    // Drop one of the basic transactions from the history.
+    let mut mismatched_txs = election_txs_1.clone();
+    let index = mismatched_txs
+        .iter()
+        .position(|hist_tx| matches!(hist_tx.data, HistoricTransactionData::Basic(_)))
+        .unwrap();
+    mismatched_txs.remove(index);
+
+    assert_eq!(
+        Blockchain::push_history_sync(
+            blockchain2.upgradable_read(),
+            election_block_1.clone(),
+            &mismatched_txs
+        ),
+        Err(PushError::InvalidBlock(BlockError::InvalidHistoryRoot))
+    );
+
+    // Neither the head nor the accounts were changed.
+    assert_eq!(blockchain2.read().head(), genesis_head);
+    assert_eq!(
+        blockchain2.read().state.accounts.get_root_hash_assert(None),
+        genesis_state_root
+    );
+
+    // The correct history is still accepted afterwards.
+    assert_eq!(
+        Blockchain::push_history_sync(
+            blockchain2.upgradable_read(),
+            election_block_1,
+            &election_txs_1
+        ),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(blockchain.head(), blockchain2.read().head());
+}
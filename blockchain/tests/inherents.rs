@@ -1,16 +1,19 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 use nimiq_account::{BlockLogger, BlockState};
 use nimiq_block::{
     Block, DoubleProposalProof, DoubleVoteProof, ForkProof, MacroBlock, MacroBody, MacroHeader,
     SkipBlockInfo,
 };
-use nimiq_blockchain::{Blockchain, BlockchainConfig};
-use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_blockchain::{
+    reward::RewardFallback, BlockProducer, Blockchain, BlockchainConfig, ChainInfoExport,
+};
+use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
 use nimiq_bls::AggregateSignature;
 use nimiq_database::{traits::WriteTransaction, volatile::VolatileDatabase};
+use nimiq_genesis_builder::GenesisBuilder;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput};
-use nimiq_keys::Address;
+use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_primitives::{
     coin::Coin,
     networks::NetworkId,
@@ -18,14 +21,21 @@ use nimiq_primitives::{
     slots_allocation::{JailedValidator, PenalizedSlot},
     TendermintIdentifier, TendermintStep, TendermintVote,
 };
+use nimiq_serde::{Deserialize, Serialize};
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     block_production::TemporaryBlockProducer,
-    blockchain::{signing_key, validator_address, voting_key},
+    blockchain::{
+        fill_micro_blocks, generate_transactions, signing_key, validator_address, validator_key,
+        voting_key, REWARD_KEY,
+    },
+    test_custom_block::{next_micro_block, BlockConfig},
 };
-use nimiq_transaction::inherent::Inherent;
+use nimiq_transaction::{inherent::Inherent, reward::RewardTransaction, Transaction};
+use nimiq_transaction_builder::TransactionBuilder;
 use nimiq_utils::time::OffsetTime;
-use nimiq_vrf::VrfSeed;
+use nimiq_vrf::{DiscreteDistribution, VrfSeed, VrfUseCase};
+use parking_lot::RwLock;
 use tokio_stream::{wrappers::BroadcastStream, StreamExt};
 
 #[test]
@@ -672,3 +682,663 @@ async fn create_fork_proof() {
     // Verify that the fork proof was generated
     assert!(fork_rx.next().await.is_some());
 }
+
+#[test]
+fn it_burns_rewards_of_finalized_batch_punished_set() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Produce a skip block in the first batch, which penalizes one slot.
+    temp_producer.next_block(vec![], false);
+    temp_producer.next_block(vec![], true);
+    while !temp_producer.blockchain.read().head().is_macro() {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let punished_set = temp_producer
+        .blockchain
+        .read()
+        .finalized_batch_punished_set()
+        .unwrap();
+    assert_eq!(punished_set.len(), 1);
+
+    // The rewards for the first batch are distributed in the next macro block.
+    for _ in 0..Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+    let reward_txs = temp_producer
+        .blockchain
+        .read()
+        .head()
+        .unwrap_macro()
+        .body
+        .unwrap()
+        .transactions;
+
+    // The reward of the punished slots is burned.
+    let reward_pot: Coin = reward_txs.iter().map(|tx| tx.value).sum();
+    let burn_tx = reward_txs.last().unwrap();
+    assert_eq!(burn_tx.recipient, Address::burn_address());
+    assert_eq!(
+        Some(burn_tx.value),
+        (reward_pot / Policy::SLOTS as u64).checked_mul(punished_set.len() as u64)
+    );
+}
+
+#[test]
+fn it_can_compute_batch_rewards() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Produce three batches. The first batch distributes no rewards.
+    for _ in 0..3 * Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(blockchain.compute_batch_rewards(0), None);
+    assert_eq!(blockchain.compute_batch_rewards(4), None);
+
+    let summary = blockchain.compute_batch_rewards(3).unwrap();
+    let reward_txs = blockchain
+        .get_block_at(Policy::macro_block_of(3).unwrap(), true, None)
+        .unwrap()
+        .unwrap_macro()
+        .body
+        .unwrap()
+        .transactions;
+
+    // The summed reward transactions equal the reward pot.
+    let total: Coin = reward_txs.iter().map(|tx| tx.value).sum();
+    assert_eq!(summary.reward_pot, total);
+    assert_eq!(summary.burned_reward, Coin::ZERO);
+    assert_eq!(
+        summary.reward_pot,
+        summary
+            .slot_reward
+            .checked_mul(Policy::SLOTS as u64)
+            .unwrap()
+            + summary.remainder
+    );
+    let validator_total: Coin = summary
+        .validator_rewards
+        .iter()
+        .map(|(_, reward)| *reward)
+        .sum();
+    assert_eq!(validator_total, summary.reward_pot);
+}
+
+#[test]
+fn it_returns_disabled_validators() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Create a fork at block 1 and report it in block 2, which jails the validator.
+    let fork1 = temp_producer
+        .next_block_no_push(vec![], false)
+        .unwrap_micro();
+    let fork2 = temp_producer.next_block(vec![0x42], false).unwrap_micro();
+    let fork_proof = ForkProof::new(
+        validator_address(),
+        fork1.header.clone(),
+        fork1.justification.unwrap().unwrap_micro(),
+        fork2.header.clone(),
+        fork2.justification.unwrap().unwrap_micro(),
+    );
+    let reporting_block = {
+        let blockchain = temp_producer.blockchain.read();
+        next_micro_block(
+            &temp_producer.producer.signing_key,
+            &blockchain,
+            &BlockConfig {
+                equivocation_proofs: vec![fork_proof.into()],
+                ..Default::default()
+            },
+        )
+    };
+    assert_eq!(
+        temp_producer.push(Block::Micro(reporting_block)),
+        Ok(PushResult::Extended)
+    );
+
+    // Finish the batch. The jailed validator's slots are disabled in the next batch.
+    while !temp_producer.blockchain.read().head().is_macro() {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(blockchain.disabled_validators(1), Some(vec![]));
+    assert_eq!(
+        blockchain.disabled_validators(2),
+        Some(vec![validator_address()])
+    );
+    assert_eq!(blockchain.disabled_validators(0), None);
+    assert_eq!(blockchain.disabled_validators(3), None);
+}
+
+#[test]
+fn it_returns_current_reward_weights() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let weights = blockchain.current_reward_weights();
+    let validators = blockchain.current_validators().unwrap();
+    assert_eq!(weights.len(), validators.num_validators());
+
+    // Every slot is accounted for.
+    let total_slots: u16 = weights.iter().map(|(_, num_slots)| *num_slots).sum();
+    assert_eq!(total_slots, Policy::SLOTS);
+
+    // The addresses are the registered reward addresses of the validators.
+    let staking_contract = blockchain.get_staking_contract();
+    let data_store = blockchain.get_staking_contract_store();
+    let db_txn = blockchain.read_transaction();
+    for ((reward_address, num_slots), validator_slot) in weights.iter().zip(validators.iter()) {
+        let validator = staking_contract
+            .get_validator(&data_store.read(&db_txn), &validator_slot.address)
+            .unwrap();
+        assert_eq!(reward_address, &validator.reward_address);
+        assert_eq!(*num_slots, validator_slot.num_slots());
+    }
+}
+
+#[test]
+fn it_computes_macro_block_inherents() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Finish the first batch, so that the next macro block distributes rewards.
+    for _ in 0..Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+    fill_micro_blocks(&temp_producer.producer, &temp_producer.blockchain);
+
+    let blockchain = temp_producer.blockchain.read();
+    let proposal = temp_producer.producer.next_macro_block_proposal(
+        &blockchain,
+        blockchain.head().timestamp() + Policy::BLOCK_SEPARATION_TIME,
+        0,
+        vec![],
+    );
+    let header = &proposal.header;
+
+    let inherents = blockchain.compute_macro_block_inherents(
+        header.seed.clone(),
+        header.block_number,
+        header.timestamp,
+    );
+    assert!(inherents
+        .iter()
+        .any(|inherent| matches!(inherent, Inherent::Reward { .. })));
+    assert_eq!(
+        inherents,
+        blockchain.create_macro_block_inherents(&proposal)
+    );
+}
+
+#[test]
+fn it_computes_block_supply_delta() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Produce a skip block in the first batch, so that a part of its reward is burned.
+    temp_producer.next_block(vec![], false);
+    temp_producer.next_block(vec![], true);
+    for _ in 0..2 * Policy::blocks_per_batch() - 2 {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let macro_block = blockchain.head();
+    assert_eq!(
+        macro_block.block_number(),
+        Policy::macro_block_of(2).unwrap()
+    );
+
+    let summary = blockchain.compute_batch_rewards(2).unwrap();
+    assert!(summary.burned_reward > Coin::ZERO);
+    assert_eq!(
+        blockchain.block_supply_delta(&macro_block.hash()),
+        Some(i128::from(u64::from(
+            summary.reward_pot - summary.burned_reward
+        )))
+    );
+
+    // Micro blocks don't change the supply.
+    let micro_block = blockchain.get_block_at(1, false, None).unwrap();
+    assert_eq!(blockchain.block_supply_delta(&micro_block.hash()), Some(0));
+
+    // Unknown blocks.
+    assert_eq!(blockchain.block_supply_delta(&Blake2bHash::default()), None);
+}
+
+fn update_reward_address_tx(reward_address: Address, validity_start_height: u32) -> Transaction {
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+    TransactionBuilder::new_update_validator(
+        &key_pair,
+        &validator_key(),
+        None,
+        None,
+        Some(reward_address),
+        None,
+        100.try_into().unwrap(),
+        validity_start_height,
+        NetworkId::UnitAlbatross,
+    )
+}
+
+fn batch_rewards_with_reward_address(
+    reward_fallback: RewardFallback,
+    reward_address: Option<Address>,
+) -> Vec<RewardTransaction> {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.blockchain.write().config.reward_fallback = reward_fallback;
+
+    // Change the reward address of the validator in the first block.
+    let mut num_blocks = 2 * Policy::blocks_per_batch();
+    if let Some(reward_address) = reward_address {
+        let tx = update_reward_address_tx(reward_address, 1 + Policy::genesis_block_number());
+        temp_producer.next_block_with_txs(vec![], false, vec![tx]);
+        num_blocks -= 1;
+    }
+    for _ in 0..num_blocks {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let macro_block = blockchain.head().unwrap_macro();
+    assert_eq!(
+        macro_block.block_number(),
+        Policy::macro_block_of(2).unwrap()
+    );
+    macro_block.body.unwrap().transactions
+}
+
+#[test]
+fn it_pays_rewards_to_the_reward_address() {
+    let transactions = batch_rewards_with_reward_address(RewardFallback::ValidatorAddress, None);
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].validator_address, validator_address());
+    assert_eq!(
+        transactions[0].recipient,
+        Address::from_user_friendly_address("NQ46 U66M JNLD 0DJ7 0E9P Q7XR V9KV H976 813A")
+            .unwrap()
+    );
+}
+
+#[test]
+fn it_burns_rewards_that_the_reward_address_rejects() {
+    let transactions = batch_rewards_with_reward_address(
+        RewardFallback::Burn,
+        Some(Policy::STAKING_CONTRACT_ADDRESS),
+    );
+
+    // The staking contract can't accept rewards, so everything is burned.
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].validator_address, Address::burn_address());
+    assert_eq!(transactions[0].recipient, Address::burn_address());
+}
+
+#[test]
+fn it_falls_back_to_the_validator_address() {
+    let transactions = batch_rewards_with_reward_address(
+        RewardFallback::ValidatorAddress,
+        Some(Policy::STAKING_CONTRACT_ADDRESS),
+    );
+
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].validator_address, validator_address());
+    assert_eq!(transactions[0].recipient, validator_address());
+}
+
+#[test]
+fn it_returns_validator_reward_history() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let reward_address =
+        Address::from_user_friendly_address("NQ46 U66M JNLD 0DJ7 0E9P Q7XR V9KV H976 813A")
+            .unwrap();
+    let other_address =
+        Address::from_user_friendly_address("NQ39 VBTN P2HX Q3MF KHF1 CCLA G6FS 9B8S VY28")
+            .unwrap();
+
+    // Epoch 1 pays the reward address, epoch 2 pays the other address and epoch 3 pays the
+    // reward address again.
+    for epoch in 1..=3 {
+        let new_reward_address = match epoch {
+            2 => Some(other_address.clone()),
+            3 => Some(reward_address.clone()),
+            _ => None,
+        };
+
+        let mut num_blocks = Policy::blocks_per_epoch();
+        if let Some(new_reward_address) = new_reward_address {
+            let block_number = temp_producer.blockchain.read().block_number();
+            let tx = update_reward_address_tx(new_reward_address, block_number);
+            temp_producer.next_block_with_txs(vec![], false, vec![tx]);
+            num_blocks -= 1;
+        }
+        for _ in 0..num_blocks {
+            temp_producer.next_block(vec![], false);
+        }
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(
+        blockchain.election_head().block_number(),
+        Policy::election_block_of(3).unwrap()
+    );
+
+    let history = blockchain.validator_reward_history(&reward_address, 0, 4);
+    assert_eq!(
+        history.iter().map(|(epoch, _)| *epoch).collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+    assert!(history.iter().all(|(_, reward)| *reward > Coin::ZERO));
+
+    let history = blockchain.validator_reward_history(&other_address, 0, 4);
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].0, 2);
+
+    // The totals match the rewards committed in the macro bodies of the epoch.
+    let batches_per_epoch = Policy::batches_per_epoch() as u32;
+    let expected = (batches_per_epoch + 1..=2 * batches_per_epoch)
+        .flat_map(|batch| {
+            blockchain
+                .compute_batch_rewards(batch)
+                .unwrap()
+                .validator_rewards
+        })
+        .fold(Coin::ZERO, |total, (_, reward)| total + reward);
+    assert_eq!(history[0].1, expected);
+
+    // Requesting a range without finalized epochs returns nothing.
+    assert!(blockchain
+        .validator_reward_history(&reward_address, 4, 10)
+        .is_empty());
+}
+
+#[test]
+fn it_previews_current_reward_pot() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    // Start right after a macro block, where no reward has accumulated yet.
+    for _ in 0..Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+    assert_eq!(
+        temp_producer.blockchain.read().current_reward_pot(),
+        Coin::ZERO
+    );
+
+    let mut previous_pot = Coin::ZERO;
+    for seed in 0..5 {
+        let block_number = temp_producer.blockchain.read().block_number() + 1;
+        let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 2, seed);
+        let block = temp_producer.next_block_with_txs(vec![], false, txs);
+        assert!(block.sum_transaction_fees() > Coin::ZERO);
+
+        let blockchain = temp_producer.blockchain.read();
+        let pot = blockchain.current_reward_pot();
+        assert!(pot > previous_pot);
+        assert!(pot >= blockchain.state.main_chain.cum_tx_fees);
+        previous_pot = pot;
+    }
+}
+
+/// Creates a block producer for a chain with two validators that share the same keys, so that
+/// one of them stays active after the other one is punished and the producer can sign for both.
+fn two_validator_producer() -> TemporaryBlockProducer {
+    let other_address =
+        Address::from_user_friendly_address("NQ39 VBTN P2HX Q3MF KHF1 CCLA G6FS 9B8S VY28")
+            .unwrap();
+    let mut genesis_builder = GenesisBuilder::default();
+    genesis_builder
+        .with_network(NetworkId::UnitAlbatross)
+        .with_genesis_block_number(Policy::genesis_block_number());
+    for address in [validator_address(), other_address] {
+        genesis_builder.with_genesis_validator(
+            address.clone(),
+            signing_key().public,
+            voting_key().public_key,
+            address,
+            None,
+            None,
+            false,
+        );
+    }
+    let genesis_info = genesis_builder
+        .generate(VolatileDatabase::new(20).unwrap())
+        .unwrap();
+    let blockchain = Blockchain::new_with_genesis(
+        VolatileDatabase::new(20).unwrap(),
+        NetworkId::UnitAlbatross,
+        genesis_info.block,
+        genesis_info.accounts,
+    )
+    .unwrap();
+    TemporaryBlockProducer {
+        blockchain: Arc::new(RwLock::new(blockchain)),
+        producer: BlockProducer::new(signing_key(), voting_key()),
+    }
+}
+
+#[test]
+fn it_returns_punishments_for_epoch() {
+    let temp_producer = two_validator_producer();
+
+    // Skip a block, which penalizes the slot that was supposed to produce it.
+    let skip_block = temp_producer.next_block(vec![], true);
+    let skip_block_number = skip_block.block_number();
+    let offender = temp_producer
+        .blockchain
+        .read()
+        .get_proposer_of(&skip_block.hash(), None)
+        .unwrap();
+
+    // Wait until the offending validator is the proposer again, and create a fork there.
+    let (fork1, fork2) = loop {
+        let is_offender = {
+            let blockchain = temp_producer.blockchain.read();
+            let block_number = blockchain.block_number() + 1;
+            assert!(!Policy::is_macro_block_at(block_number));
+            blockchain
+                .get_proposer(
+                    block_number,
+                    block_number,
+                    blockchain.head().seed().entropy(),
+                    None,
+                )
+                .unwrap()
+                .validator
+                .address
+                == offender.validator.address
+        };
+        if is_offender {
+            let fork1 = temp_producer
+                .next_block_no_push(vec![], false)
+                .unwrap_micro();
+            let fork2 = temp_producer.next_block(vec![0x42], false).unwrap_micro();
+            break (fork1, fork2);
+        }
+        temp_producer.next_block(vec![], false);
+    };
+    let fork_block_number = fork1.block_number();
+
+    // Report the fork, which jails the offending validator.
+    let fork_proof = ForkProof::new(
+        offender.validator.address.clone(),
+        fork1.header.clone(),
+        fork1.justification.unwrap().unwrap_micro(),
+        fork2.header.clone(),
+        fork2.justification.unwrap().unwrap_micro(),
+    );
+    let reporting_block = {
+        let blockchain = temp_producer.blockchain.read();
+        next_micro_block(
+            &temp_producer.producer.signing_key,
+            &blockchain,
+            &BlockConfig {
+                equivocation_proofs: vec![fork_proof.into()],
+                ..Default::default()
+            },
+        )
+    };
+    assert_eq!(
+        temp_producer.push(Block::Micro(reporting_block)),
+        Ok(PushResult::Extended)
+    );
+
+    // The epoch is not finalized yet.
+    assert_eq!(
+        temp_producer.blockchain.read().punishments_for_epoch(1),
+        None
+    );
+
+    // Finish the epoch.
+    while !temp_producer.blockchain.read().head().is_election() {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    let punishments = blockchain.punishments_for_epoch(1).unwrap();
+    assert_eq!(
+        punishments.penalized_slots,
+        vec![PenalizedSlot {
+            slot: offender.number,
+            validator_address: offender.validator.address.clone(),
+            offense_event_block: skip_block_number,
+        }]
+    );
+    assert_eq!(
+        punishments.jailed_validators,
+        vec![JailedValidator {
+            slots: offender.validator.slots.clone(),
+            validator_address: offender.validator.address.clone(),
+            offense_event_block: fork_block_number,
+        }]
+    );
+    assert_eq!(
+        punishments.next_epoch_punished_set,
+        blockchain
+            .election_head()
+            .body
+            .unwrap()
+            .next_batch_initial_punished_set
+    );
+
+    assert_eq!(blockchain.punishments_for_epoch(0), None);
+    assert_eq!(blockchain.punishments_for_epoch(2), None);
+
+    // Without the epoch's history, the punishments are unavailable.
+    let mut txn = blockchain.write_transaction();
+    blockchain.history_store.remove_history(&mut txn, 1);
+    txn.commit();
+    assert_eq!(blockchain.punishments_for_epoch(1), None);
+}
+
+#[test]
+fn it_returns_reward_distribution_seed() {
+    let temp_producer = two_validator_producer();
+
+    // Produce three batches. The first batch distributes no rewards.
+    for _ in 0..3 * Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+
+    let blockchain = temp_producer.blockchain.read();
+    assert_eq!(blockchain.reward_distribution_seed(0), None);
+    assert_eq!(blockchain.reward_distribution_seed(4), None);
+
+    let macro_block = blockchain
+        .get_block_at(Policy::macro_block_of(3).unwrap(), true, None)
+        .unwrap()
+        .unwrap_macro();
+    let seed = blockchain.reward_distribution_seed(3).unwrap();
+    assert_eq!(seed, macro_block.header.seed);
+
+    // Reproduce the sampling of the slot that receives the remainder. No slot was punished, so
+    // every validator is eligible with all of its slots.
+    let summary = blockchain.compute_batch_rewards(3).unwrap();
+    let validators = blockchain.current_validators().unwrap();
+    assert_eq!(validators.num_validators(), 2);
+    let num_slots: Vec<u64> = validators
+        .iter()
+        .map(|validator| validator.num_slots() as u64)
+        .collect();
+    let mut rng = seed.rng(VrfUseCase::RewardDistribution);
+    let index = DiscreteDistribution::new(&num_slots).sample(&mut rng);
+
+    let reward_txs = macro_block.body.unwrap().transactions;
+    assert_eq!(reward_txs.len(), num_slots.len());
+    for (i, tx) in reward_txs.iter().enumerate() {
+        let mut expected = summary.slot_reward.checked_mul(num_slots[i]).unwrap();
+        if i == index {
+            expected += summary.remainder;
+        }
+        assert_eq!(tx.value, expected);
+    }
+}
+
+#[test]
+fn it_exports_chain_info_with_punished_slots() {
+    let temp_producer = two_validator_producer();
+
+    // Skip a block, which penalizes the slot that was supposed to produce it, and finish the batch.
+    let skip_block = temp_producer.next_block(vec![], true);
+    let mut macro_block = temp_producer.next_block(vec![], false);
+    while !macro_block.is_macro() {
+        macro_block = temp_producer.next_block(vec![], false);
+    }
+    let micro_block = temp_producer.next_block(vec![], false);
+
+    let blockchain = temp_producer.blockchain.read();
+    let punished_set = blockchain
+        .get_block(&macro_block.hash(), true, None)
+        .unwrap()
+        .unwrap_macro()
+        .body
+        .unwrap()
+        .next_batch_initial_punished_set;
+    let punished_slots: Vec<u16> = punished_set.iter().map(|slot| slot as u16).collect();
+    assert!(!punished_slots.is_empty());
+
+    // The skip block was produced before any slot was punished.
+    let export = blockchain
+        .get_chain_info_export(&skip_block.hash())
+        .unwrap();
+    assert_eq!(export.block_number, skip_block.block_number());
+    assert!(export.on_main_chain);
+    assert!(export.disabled_slots.is_empty());
+    assert_eq!(export.next_batch_punished_slots, None);
+
+    // The macro block commits to the punished slots of the next batch.
+    let export = blockchain
+        .get_chain_info_export(&macro_block.hash())
+        .unwrap();
+    assert_eq!(export.batch_number, macro_block.batch_number());
+    assert_eq!(
+        export.next_batch_punished_slots,
+        Some(punished_slots.clone())
+    );
+
+    // The punished slots are disabled for the blocks of the next batch.
+    let export = blockchain
+        .get_chain_info_export(&micro_block.hash())
+        .unwrap();
+    assert_eq!(export.disabled_slots, punished_slots);
+    assert_eq!(
+        export.cum_tx_fees,
+        blockchain
+            .get_chain_info(&micro_block.hash(), false, None)
+            .unwrap()
+            .cum_tx_fees
+    );
+    assert_eq!(
+        ChainInfoExport::deserialize_from_vec(&export.serialize_to_vec()).unwrap(),
+        export
+    );
+
+    // Unknown blocks have no chain info.
+    assert_eq!(
+        blockchain.get_chain_info_export(&Blake2bHash::default()),
+        None
+    );
+}
@@ -1,25 +1,26 @@
-use std::path::Path;
+use std::{path::Path, sync::Arc};
 
 use nimiq_block::{
-    Block, BlockError, DoubleProposalProof, DoubleVoteProof, EquivocationProofError, ForkProof,
+    Block, BlockError, DoubleProposalProof, DoubleVoteProof, EquivocationProof,
+    EquivocationProofError, ForkProof, MicroHeader,
 };
 use nimiq_blockchain::Blockchain;
 use nimiq_blockchain_interface::{
-    AbstractBlockchain, PushError,
+    AbstractBlockchain, BlockClassification, PushError,
     PushError::{InvalidBlock, InvalidEquivocationProof},
-    PushResult,
+    PushInfo, PushResult,
 };
 use nimiq_bls::AggregateSignature;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput};
 use nimiq_keys::KeyPair;
 use nimiq_primitives::{
-    key_nibbles::KeyNibbles, networks::NetworkId, policy::Policy, TendermintIdentifier,
-    TendermintStep,
+    key_nibbles::KeyNibbles, networks::NetworkId, policy::Policy, slots_allocation::Validators,
+    TendermintIdentifier, TendermintStep,
 };
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     block_production::TemporaryBlockProducer,
-    blockchain::validator_address,
+    blockchain::{produce_macro_blocks, signing_key, validator_address},
     test_custom_block::{next_macro_block, next_micro_block, next_skip_block, BlockConfig},
     test_rng::test_rng,
     zkp_test_data::{get_base_seed, simulate_merger_wrapper, ZKP_TEST_KEYS_PATH},
@@ -325,6 +326,22 @@ fn it_validates_block_number() {
     );
 }
 
+#[test]
+#[should_panic(expected = "Block number offset -2147483648 out of range")]
+fn it_rejects_out_of_range_block_number_offsets() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let blockchain = temp_producer.blockchain.read();
+
+    next_micro_block(
+        &signing_key(),
+        &blockchain,
+        &BlockConfig {
+            block_number_offset: i32::MIN,
+            ..Default::default()
+        },
+    );
+}
+
 #[test]
 fn it_validates_block_time() {
     expect_push_micro_block(
@@ -336,6 +353,40 @@ fn it_validates_block_time() {
     );
 }
 
+#[test]
+fn it_validates_timestamp_drift() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    let block = {
+        let blockchain = temp_producer.blockchain.read();
+        Block::Micro(temp_producer.producer.next_micro_block(
+            &blockchain,
+            blockchain.time.now() + 10_000,
+            vec![],
+            vec![],
+            vec![],
+            None,
+        ))
+    };
+
+    // The block is rejected if it is too far in the future.
+    temp_producer.blockchain.write().config.max_timestamp_drift = Some(1_000);
+    assert_eq!(
+        temp_producer.push(block.clone()),
+        Err(InvalidBlock(BlockError::InvalidTimestamp))
+    );
+
+    temp_producer.blockchain.write().config.max_timestamp_drift = Some(Policy::TIMESTAMP_MAX_DRIFT);
+    assert_eq!(
+        temp_producer.blockchain.read().dry_run_verify_block(&block),
+        Ok(())
+    );
+
+    // By default, the timestamp is not checked against the local time.
+    temp_producer.blockchain.write().config.max_timestamp_drift = None;
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+}
+
 #[test]
 fn it_validates_body_hash() {
     expect_push_micro_block(
@@ -391,6 +442,8 @@ fn it_validates_history_root() {
 
     push_rebranch(&config, &Err(PushError::InvalidFork));
 
+    simply_push_macro_block(&config, &Err(InvalidBlock(BlockError::InvalidHistoryRoot)));
+
     push_rebranch_across_epochs(&config);
 }
 
@@ -468,6 +521,68 @@ fn it_validates_fork_proofs() {
     )
 }
 
+#[test]
+fn it_rejects_fork_proofs_with_identical_headers() {
+    let header = TemporaryBlockProducer::new()
+        .next_block(vec![], false)
+        .unwrap_micro()
+        .header;
+    let header_hash: Blake2bHash = header.hash();
+    let justification = signing_key().sign(header_hash.as_bytes());
+
+    // Two identical headers don't constitute a fork, even if they are validly signed.
+    expect_push_micro_block(
+        BlockConfig {
+            equivocation_proofs: vec![ForkProof::new(
+                validator_address(),
+                header.clone(),
+                justification.clone(),
+                header,
+                justification,
+            )
+            .into()],
+            test_macro: false,
+            test_election: false,
+            ..Default::default()
+        },
+        Err(InvalidEquivocationProof(EquivocationProofError::SameHeader)),
+    )
+}
+
+#[test]
+fn it_validates_punishment_inherents_of_fork_proofs() {
+    let header1 = TemporaryBlockProducer::new()
+        .next_block(vec![], false)
+        .unwrap_micro()
+        .header;
+    let mut header2 = header1.clone();
+    header2.timestamp += 1;
+    let header1_hash: Blake2bHash = header1.hash();
+    let header2_hash: Blake2bHash = header2.hash();
+    let justification1 = signing_key().sign(header1_hash.as_bytes());
+    let justification2 = signing_key().sign(header2_hash.as_bytes());
+
+    let mut config = BlockConfig {
+        equivocation_proofs: vec![ForkProof::new(
+            validator_address(),
+            header1,
+            justification1,
+            header2,
+            justification2,
+        )
+        .into()],
+        ..Default::default()
+    };
+    push_micro_after_micro(&config, &Ok(PushResult::Extended));
+
+    // The block's state must include the punishment of the fork proof.
+    config.omit_punishment_inherents = true;
+    push_micro_after_micro(
+        &config,
+        &Err(InvalidBlock(BlockError::AccountsHashMismatch)),
+    );
+}
+
 #[test]
 fn it_validates_double_proposal_proofs() {
     let mut rng = test_rng(true);
@@ -555,6 +670,41 @@ fn it_validates_double_vote_proofs() {
     )
 }
 
+#[test]
+fn it_can_dry_run_verify_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // A valid block is accepted without modifying the chain.
+    let block = temp_producer.next_block_no_push(vec![], false);
+    let head_hash = temp_producer.blockchain.read().head_hash();
+    assert_eq!(
+        temp_producer.blockchain.read().dry_run_verify_block(&block),
+        Ok(())
+    );
+    assert_eq!(temp_producer.blockchain.read().head_hash(), head_hash);
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Extended));
+
+    // A tampered block is rejected with the same error as when pushing it.
+    let block = {
+        let blockchain = temp_producer.blockchain.read();
+        let config = BlockConfig {
+            body_hash: Some(Blake2sHash::default()),
+            ..Default::default()
+        };
+        Block::Micro(next_micro_block(
+            &temp_producer.producer.signing_key,
+            &blockchain,
+            &config,
+        ))
+    };
+    let expected_err = Err(InvalidBlock(BlockError::BodyHashMismatch));
+    assert_eq!(
+        temp_producer.blockchain.read().dry_run_verify_block(&block),
+        expected_err
+    );
+    assert_eq!(temp_producer.push(block).map(|_| ()), expected_err);
+}
+
 #[test]
 fn can_push_zkps() {
     let temp_producer1 = TemporaryBlockProducer::new();
@@ -612,3 +762,296 @@ fn can_push_zkps() {
         );
     }
 }
+
+#[test]
+fn it_can_push_blocks_in_batches() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    let block1 = temp_producer2.next_block(vec![], false);
+    let block2 = temp_producer2.next_block(vec![], false);
+    let invalid_block = {
+        let blockchain = temp_producer2.blockchain.read();
+        let config = BlockConfig {
+            version: Some(Policy::VERSION - 1),
+            ..Default::default()
+        };
+        Block::Micro(next_micro_block(
+            &temp_producer2.producer.signing_key,
+            &blockchain,
+            &config,
+        ))
+    };
+    let block3 = temp_producer2.next_block(vec![], false);
+
+    // Orphan and known blocks do not stop the batch, the invalid block does.
+    let results = Blockchain::push_blocks(
+        temp_producer1.blockchain.upgradable_read(),
+        vec![
+            block1.clone(),
+            block3.clone(),
+            block2.clone(),
+            block1,
+            invalid_block,
+            block3,
+        ],
+    );
+    assert_eq!(
+        results,
+        vec![
+            Ok(PushResult::Extended),
+            Err(PushError::Orphan),
+            Ok(PushResult::Extended),
+            Ok(PushResult::Known),
+            Err(InvalidBlock(BlockError::UnsupportedVersion)),
+        ]
+    );
+    assert_eq!(temp_producer1.blockchain.read().head_hash(), block2.hash());
+}
+
+#[test]
+fn it_validates_fork_proof_structure() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let header = temp_producer
+        .next_block(vec![], false)
+        .unwrap_micro()
+        .header;
+    let next_header = temp_producer
+        .next_block(vec![], false)
+        .unwrap_micro()
+        .header;
+
+    let fork_proof = |header1: MicroHeader, header2: MicroHeader| {
+        let header1_hash: Blake2bHash = header1.hash();
+        let header2_hash: Blake2bHash = header2.hash();
+        ForkProof::new(
+            validator_address(),
+            header1,
+            signing_key().sign(header1_hash.as_bytes()),
+            header2,
+            signing_key().sign(header2_hash.as_bytes()),
+        )
+    };
+    let blockchain = temp_producer.blockchain.read();
+
+    // Two headers for the same slot.
+    let mut other_header = header.clone();
+    other_header.timestamp += 1;
+    assert_eq!(
+        blockchain.validate_fork_proof_structure(&fork_proof(header.clone(), other_header)),
+        Ok(())
+    );
+
+    // Mismatched block numbers.
+    let mut other_header = header.clone();
+    other_header.block_number += 1;
+    assert_eq!(
+        blockchain.validate_fork_proof_structure(&fork_proof(header.clone(), other_header)),
+        Err(InvalidEquivocationProof(
+            EquivocationProofError::SlotMismatch
+        ))
+    );
+
+    // Mismatched seeds, i.e. different slots at the same block number.
+    let mut other_header = next_header;
+    other_header.block_number = header.block_number;
+    assert_eq!(
+        blockchain.validate_fork_proof_structure(&fork_proof(header.clone(), other_header)),
+        Err(InvalidEquivocationProof(
+            EquivocationProofError::SlotMismatch
+        ))
+    );
+
+    // Identical headers.
+    assert_eq!(
+        blockchain.validate_fork_proof_structure(&fork_proof(header.clone(), header)),
+        Err(InvalidEquivocationProof(EquivocationProofError::SameHeader))
+    );
+}
+
+#[test]
+fn it_can_find_fork_proofs_at_a_block_number() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0]
+    //    \- [0]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block.clone()).unwrap();
+    temp_producer1.next_block(vec![0x48], false);
+    let fork = temp_producer2.next_block(vec![], false);
+    assert_eq!(temp_producer1.push(fork.clone()), Ok(PushResult::Forked));
+
+    let blockchain = temp_producer1.blockchain.read();
+    assert!(blockchain
+        .find_fork_proofs_at(block.block_number())
+        .is_empty());
+
+    let proofs = blockchain.find_fork_proofs_at(fork.block_number());
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].block_number(), fork.block_number());
+    assert_eq!(proofs[0].validator_address(), &validator_address());
+
+    // The proof is well-formed and validly signed.
+    let validators = blockchain.current_validators().unwrap();
+    let proof: EquivocationProof = proofs[0].clone().into();
+    assert_eq!(proof.verify(NetworkId::UnitAlbatross, &validators), Ok(()));
+}
+
+#[test]
+fn it_can_audit_known_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let block = temp_producer.next_block(vec![], false);
+    let next_block = temp_producer.next_block(vec![], false);
+
+    // A known block is verified again in audit mode.
+    assert_eq!(temp_producer.push(block.clone()), Ok(PushResult::Known));
+    temp_producer.blockchain.write().config.audit_known_blocks = true;
+    assert_eq!(temp_producer.push(block.clone()), Ok(PushResult::Known));
+
+    // Corrupt the justification of the stored block. This doesn't change the block hash, so the
+    // block is still known.
+    {
+        let blockchain = temp_producer.blockchain.read();
+        let mut chain_info = blockchain
+            .chain_store
+            .get_chain_info(&block.hash(), true, None)
+            .unwrap();
+        let mut corrupted_block = chain_info.head.unwrap_micro();
+        corrupted_block.justification = next_block.unwrap_micro().justification;
+        chain_info.head = Block::Micro(corrupted_block);
+        assert_eq!(chain_info.head.hash(), block.hash());
+
+        let mut txn = blockchain.write_transaction();
+        blockchain
+            .chain_store
+            .put_chain_info(&mut txn, &block.hash(), &chain_info, true);
+        txn.commit();
+    }
+
+    // In audit mode the corrupted store surfaces when the honest block is pushed again.
+    assert!(matches!(
+        temp_producer.push(block.clone()),
+        Err(InvalidBlock(_))
+    ));
+
+    // By default, known blocks are not verified again.
+    temp_producer.blockchain.write().config.audit_known_blocks = false;
+    assert_eq!(temp_producer.push(block), Ok(PushResult::Known));
+}
+
+#[test]
+fn it_can_classify_blocks() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    let block = temp_producer1.next_block(vec![], false);
+    assert_eq!(temp_producer2.push(block), Ok(PushResult::Extended));
+
+    // Two competing blocks at the same height, a regular block and a skip block.
+    let regular_block = temp_producer1.next_block_no_push(vec![], false);
+    let skip_block = temp_producer1.next_block_no_push(vec![], true);
+    {
+        let blockchain = temp_producer1.blockchain.read();
+        assert_eq!(
+            blockchain.classify_block(&regular_block),
+            Ok(BlockClassification::Extend)
+        );
+        assert_eq!(
+            blockchain.classify_block(&skip_block),
+            Ok(BlockClassification::Extend)
+        );
+    }
+
+    // The chain with the earlier skip block wins over the regular block.
+    assert_eq!(
+        temp_producer1.push(skip_block.clone()),
+        Ok(PushResult::Extended)
+    );
+    {
+        let blockchain = temp_producer1.blockchain.read();
+        assert_eq!(
+            blockchain.classify_block(&skip_block),
+            Ok(BlockClassification::Known)
+        );
+        assert_eq!(
+            blockchain.classify_block(&regular_block),
+            Ok(BlockClassification::Inferior)
+        );
+    }
+
+    assert_eq!(
+        temp_producer2.push(regular_block.clone()),
+        Ok(PushResult::Extended)
+    );
+    assert_eq!(
+        temp_producer2.blockchain.read().classify_block(&skip_block),
+        Ok(BlockClassification::Better)
+    );
+
+    // Classifying doesn't push anything.
+    assert_eq!(
+        temp_producer2.blockchain.read().head_hash(),
+        regular_block.hash()
+    );
+}
+
+#[test]
+fn it_verifies_blocks_against_the_validators_of_their_epoch() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let skip_block = temp_producer.next_block(vec![], true);
+    assert!(skip_block.is_skip());
+
+    produce_macro_blocks(
+        &temp_producer.producer,
+        &temp_producer.blockchain,
+        Policy::batches_per_epoch() as usize * 3,
+    );
+
+    // Replace the current validators, so that they differ from the validators of the skip block's
+    // epoch.
+    let mut blockchain = temp_producer.blockchain.write();
+    assert!(Policy::epoch_at(skip_block.block_number()) + 1 < blockchain.epoch_number());
+    blockchain.state.current_slots = Some(Arc::new(Validators::default()));
+
+    // The skip block proof is verified against the validators of the skip block's epoch.
+    assert_eq!(blockchain.dry_run_verify_block(&skip_block), Ok(()));
+}
+
+#[test]
+fn it_reports_push_info() {
+    let temp_producer = TemporaryBlockProducer::new();
+
+    // Push a full epoch, covering micro, macro and election blocks.
+    for _ in 0..Policy::blocks_per_epoch() {
+        let block = temp_producer.next_block_no_push(vec![], false);
+        let block_number = block.block_number();
+
+        let info =
+            Blockchain::push_with_info(temp_producer.blockchain.upgradable_read(), block.clone());
+        let expected = PushInfo {
+            result: PushResult::Extended,
+            is_macro: Policy::is_macro_block_at(block_number),
+            is_election: Policy::is_election_block_at(block_number),
+            epoch: Policy::epoch_at(block_number + 1),
+        };
+        assert_eq!(info, Ok(expected.clone()));
+
+        // Pushing the block again reports the same block information. Macro blocks are ignored
+        // since they are already finalized.
+        let result = if expected.is_macro {
+            PushResult::Ignored
+        } else {
+            PushResult::Known
+        };
+        let info = Blockchain::push_with_info(temp_producer.blockchain.upgradable_read(), block);
+        assert_eq!(info, Ok(PushInfo { result, ..expected }));
+    }
+
+    // The last block pushed was an election block, which starts a new epoch.
+    let blockchain = temp_producer.blockchain.read();
+    assert!(blockchain.head().is_election());
+    assert_eq!(
+        Policy::epoch_at(blockchain.block_number() + 1),
+        blockchain.epoch_number() + 1
+    );
+}
@@ -1,4 +1,5 @@
 use nimiq_account::RevertInfo;
+use nimiq_blockchain::Blockchain;
 use nimiq_blockchain_interface::{
     AbstractBlockchain, ChunksPushError, ChunksPushResult, PushResult,
 };
@@ -186,6 +187,54 @@ fn can_push_valid_chunks() {
     );
 }
 
+#[test]
+fn can_verify_accounts_chunks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    temp_producer.next_block(vec![], false);
+
+    let expected_root = temp_producer
+        .blockchain
+        .read()
+        .state
+        .accounts
+        .get_root_hash_assert(None);
+
+    // Split the whole trie into small chunks.
+    let mut chunks = vec![];
+    let mut start_key = Some(KeyNibbles::ROOT);
+    while let Some(start) = start_key {
+        let chunk = temp_producer.get_chunk(start, 1).chunk;
+        start_key = chunk.end_key.clone();
+        chunks.push(chunk);
+    }
+    assert!(chunks.len() >= 3);
+
+    // The complete set of chunks.
+    assert!(Blockchain::verify_accounts_chunks(&chunks, &expected_root));
+
+    // A set of chunks with a gap.
+    let mut with_gap = chunks.clone();
+    with_gap.remove(1);
+    assert!(!Blockchain::verify_accounts_chunks(
+        &with_gap,
+        &expected_root
+    ));
+
+    // A set of chunks out of order.
+    let mut out_of_order = chunks.clone();
+    out_of_order.swap(0, 1);
+    assert!(!Blockchain::verify_accounts_chunks(
+        &out_of_order,
+        &expected_root
+    ));
+
+    // An incomplete set of chunks.
+    assert!(!Blockchain::verify_accounts_chunks(
+        &chunks[..chunks.len() - 1],
+        &expected_root
+    ));
+}
+
 #[test]
 fn can_ignore_chunks_with_invalid_start_key() {
     let temp_producer1 = TemporaryBlockProducer::new();
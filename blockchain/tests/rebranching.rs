@@ -1,7 +1,17 @@
-use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
-use nimiq_primitives::policy::Policy;
+use std::str::FromStr;
+
+use nimiq_block::Block;
+use nimiq_blockchain_interface::{
+    AbstractBlockchain, BlockchainEvent, ChainAnalysis, ChainOrdering, PushError, PushResult,
+};
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
 use nimiq_test_log::test;
-use nimiq_test_utils::block_production::TemporaryBlockProducer;
+use nimiq_test_utils::{
+    block_production::TemporaryBlockProducer,
+    blockchain::{generate_transactions, REWARD_KEY},
+};
 
 #[test]
 fn it_can_rebranch_skip_block() {
@@ -232,3 +242,478 @@ fn it_can_rebranch_to_inferior_macro_block() {
         blockchain2.state.previous_slots
     );
 }
+
+#[test]
+fn it_rejects_rebranches_exceeding_max_depth() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0] - [0]
+    //    \- [1]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    for _ in 0..3 {
+        temp_producer1.next_block(vec![], false);
+    }
+    let fork = temp_producer2.next_block(vec![], true);
+
+    // Rebranching to the fork would revert three blocks.
+    temp_producer1.blockchain.write().config.max_rebranch_depth = Some(2);
+    assert_eq!(
+        temp_producer1.push(fork.clone()),
+        Err(PushError::InvalidFork)
+    );
+
+    temp_producer1.blockchain.write().config.max_rebranch_depth = Some(3);
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Rebranched));
+}
+
+#[test]
+fn it_lists_blocks_adopted_by_rebranch() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0]
+    //    \- [0] - [0] - [0]
+    temp_producer1.next_block(vec![], false);
+    let main2 = temp_producer1.next_block(vec![], false);
+
+    let fork1 = temp_producer2.next_block(vec![0x42], false);
+    let fork2 = temp_producer2.next_block(vec![], false);
+    let fork3 = temp_producer2.next_block(vec![], false);
+
+    assert_eq!(temp_producer1.push(fork1.clone()), Ok(PushResult::Forked));
+    assert_eq!(temp_producer1.push(fork2.clone()), Ok(PushResult::Forked));
+
+    let blockchain = temp_producer1.blockchain.read();
+    assert_eq!(
+        blockchain.fork_adopt_list(&fork2.hash()),
+        Some(vec![fork1.hash(), fork2.hash()])
+    );
+    assert_eq!(blockchain.fork_adopt_list(&main2.hash()), Some(vec![]));
+    assert_eq!(blockchain.fork_adopt_list(&fork3.hash()), None);
+    let mut expected_adopted = blockchain.fork_adopt_list(&fork2.hash()).unwrap();
+    expected_adopted.push(fork3.hash());
+    let mut rx = blockchain.notifier.subscribe();
+    drop(blockchain);
+
+    assert_eq!(temp_producer1.push(fork3), Ok(PushResult::Rebranched));
+    match rx.try_recv() {
+        Ok(BlockchainEvent::Rebranched(_, adopted_blocks)) => {
+            let adopted: Vec<_> = adopted_blocks.into_iter().map(|(hash, _)| hash).collect();
+            assert_eq!(adopted, expected_adopted);
+        }
+        event => panic!("Unexpected event: {:?}", event),
+    }
+}
+
+#[test]
+fn it_finds_common_ancestor_of_forks() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0] - [0]
+    //          \- [0]
+    let ancestor = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(ancestor.clone()).unwrap();
+
+    let main1 = temp_producer1.next_block(vec![], false);
+    let main2 = temp_producer1.next_block(vec![], false);
+    let fork = temp_producer2.next_block(vec![0x42], false);
+    assert_eq!(temp_producer1.push(fork.clone()), Ok(PushResult::Forked));
+
+    let blockchain = temp_producer1.blockchain.read();
+    assert_eq!(
+        blockchain.common_ancestor(&main2.hash(), &fork.hash()),
+        Some(ancestor.hash())
+    );
+    assert_eq!(
+        blockchain.common_ancestor(&fork.hash(), &main1.hash()),
+        Some(ancestor.hash())
+    );
+    assert_eq!(
+        blockchain.common_ancestor(&main2.hash(), &main1.hash()),
+        Some(main1.hash())
+    );
+    assert_eq!(
+        blockchain.common_ancestor(&main2.hash(), &Blake2bHash::default()),
+        None
+    );
+}
+
+#[test]
+fn it_prunes_revert_infos_beyond_max_rebranch_depth() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    {
+        let mut blockchain = temp_producer1.blockchain.write();
+        blockchain.config.max_rebranch_depth = Some(2);
+        blockchain.config.prune_revert_infos = true;
+    }
+
+    // ... - [0] - [0] - [0]
+    //          \- [1]
+    for _ in 0..3 {
+        let block = temp_producer1.next_block(vec![], false);
+        temp_producer2.push(block).unwrap();
+    }
+    temp_producer1.next_block(vec![], false);
+    temp_producer1.next_block(vec![], false);
+    let fork = temp_producer2.next_block(vec![], true);
+
+    // Only the revert infos of the blocks within the maximum rebranch depth are kept.
+    {
+        let blockchain = temp_producer1.blockchain.read();
+        let head_number = blockchain.block_number();
+        for block_number in head_number - 4..=head_number {
+            let revert_info = blockchain.chain_store.get_revert_info(block_number, None);
+            assert_eq!(revert_info.is_some(), block_number > head_number - 2);
+        }
+    }
+
+    // A rebranch within the maximum depth still succeeds.
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Rebranched));
+}
+
+#[test]
+fn it_can_analyze_chain_ordering() {
+    let genesis_block_number = Policy::genesis_block_number();
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0]
+    //    \- [1]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    let inferior1 = temp_producer1.next_block(vec![], false);
+    temp_producer1.next_block(vec![], false);
+    let fork = temp_producer2.next_block(vec![], true);
+
+    // A block on top of the head extends the chain.
+    let extend = temp_producer1.next_block_no_push(vec![], false);
+    let analysis = temp_producer1
+        .blockchain
+        .read()
+        .analyze_chain_ordering(&extend);
+    assert_eq!(analysis.ordering, ChainOrdering::Extend);
+    assert_eq!(analysis.fork_height, None);
+
+    // A fork with an earlier skip block is superior.
+    let analysis = temp_producer1
+        .blockchain
+        .read()
+        .analyze_chain_ordering(&fork);
+    assert_eq!(
+        analysis,
+        ChainAnalysis {
+            ordering: ChainOrdering::Superior,
+            fork_height: Some(genesis_block_number + 1),
+            our_skip_block: None,
+            their_skip_block: Some(genesis_block_number + 2),
+        }
+    );
+
+    // A fork without the skip block is inferior.
+    let analysis = temp_producer2
+        .blockchain
+        .read()
+        .analyze_chain_ordering(&inferior1);
+    assert_eq!(
+        analysis,
+        ChainAnalysis {
+            ordering: ChainOrdering::Inferior,
+            fork_height: Some(genesis_block_number + 1),
+            our_skip_block: Some(genesis_block_number + 2),
+            their_skip_block: None,
+        }
+    );
+
+    // Analysing does not push the blocks.
+    assert_eq!(
+        temp_producer1.blockchain.read().block_number(),
+        genesis_block_number + 3
+    );
+}
+
+#[test]
+fn it_reports_reverted_fees_on_rebranch() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    // [0] - [0] - [0]
+    //    \- [1]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    let mut reverted_fees = Coin::ZERO;
+    for seed in 0..2 {
+        let block_number = temp_producer1.blockchain.read().block_number() + 1;
+        let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 3, seed);
+        let block = temp_producer1.next_block_with_txs(vec![], false, txs);
+        reverted_fees += block.sum_transaction_fees();
+    }
+    assert!(reverted_fees > Coin::ZERO);
+    let fork = temp_producer2.next_block(vec![], true);
+
+    let mut rx = temp_producer1.blockchain.read().notifier.subscribe();
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Rebranched));
+
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(BlockchainEvent::Rebranched(reverted, _)) if reverted.len() == 2
+    ));
+    assert_eq!(
+        rx.try_recv(),
+        Ok(BlockchainEvent::RebranchedWithFees(reverted_fees))
+    );
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn it_records_rebranch_metrics() {
+    use prometheus_client::{encoding::text::encode, registry::Registry};
+
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0]
+    //    \- [0] - [1]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    temp_producer1.next_block(vec![0x48], false);
+    temp_producer1.next_block(vec![], false);
+    let fork1 = temp_producer2.next_block(vec![], false);
+    let fork2 = temp_producer2.next_block(vec![], true);
+
+    assert_eq!(temp_producer1.push(fork1), Ok(PushResult::Forked));
+    assert_eq!(temp_producer1.push(fork2), Ok(PushResult::Rebranched));
+
+    let mut registry = Registry::default();
+    temp_producer1
+        .blockchain
+        .read()
+        .metrics()
+        .register(&mut registry);
+    let mut encoded = String::new();
+    encode(&mut encoded, &registry).unwrap();
+
+    assert!(encoded.contains("rebranch_depth_sum 2.0"));
+    assert!(encoded.contains("rebranch_depth_count 1"));
+    assert!(encoded.contains("rebranch_reverted_blocks_sum 2.0"));
+    assert!(encoded.contains("rebranch_adopted_blocks_sum 2.0"));
+}
+
+#[test]
+fn it_can_roll_back_micro_blocks() {
+    let temp_producer = TemporaryBlockProducer::new();
+    let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+    // Complete the first batch, so that there is a macro block to stop at.
+    for _ in 0..Policy::blocks_per_batch() {
+        temp_producer.next_block(vec![], false);
+    }
+    let macro_block_number = temp_producer.blockchain.read().block_number();
+    assert!(Policy::is_macro_block_at(macro_block_number));
+
+    let mut blocks = vec![];
+    for seed in 0..4 {
+        let block_number = temp_producer.blockchain.read().block_number() + 1;
+        let txs = generate_transactions(&key_pair, block_number, NetworkId::UnitAlbatross, 2, seed);
+        blocks.push(temp_producer.next_block_with_txs(vec![], false, txs));
+    }
+
+    let mut events = temp_producer.blockchain.read().notifier.subscribe();
+
+    // Roll back the last three blocks.
+    let target = &blocks[0];
+    temp_producer
+        .blockchain
+        .write()
+        .rollback_to(target.block_number())
+        .unwrap();
+
+    {
+        let blockchain = temp_producer.blockchain.read();
+        assert_eq!(blockchain.block_number(), target.block_number());
+        assert_eq!(blockchain.head_hash(), target.hash());
+        assert_eq!(
+            blockchain.state.accounts.get_root_hash_assert(None),
+            *target.state_root()
+        );
+        assert!(!blockchain.is_on_main_chain(&blocks[1].hash()));
+        assert_eq!(blockchain.verify_state_consistency(), Ok(()));
+    }
+
+    let reverted_blocks = blocks[1..]
+        .iter()
+        .map(|block| (block.hash(), block.clone()))
+        .collect();
+    assert_eq!(
+        events.try_recv(),
+        Ok(BlockchainEvent::Rebranched(reverted_blocks, vec![]))
+    );
+    let reverted_fees = blocks[1..].iter().fold(Coin::ZERO, |fees, block| {
+        fees + block.sum_transaction_fees()
+    });
+    assert_eq!(
+        events.try_recv(),
+        Ok(BlockchainEvent::RebranchedWithFees(reverted_fees))
+    );
+
+    // Macro blocks can't be rolled back.
+    assert_eq!(
+        temp_producer
+            .blockchain
+            .write()
+            .rollback_to(macro_block_number - 1),
+        Err(PushError::InvalidFork)
+    );
+
+    // Rolling back to the macro block works and the chain can be extended afterwards.
+    temp_producer
+        .blockchain
+        .write()
+        .rollback_to(macro_block_number)
+        .unwrap();
+    assert_eq!(
+        temp_producer.blockchain.read().block_number(),
+        macro_block_number
+    );
+    temp_producer.next_block(vec![], false);
+    assert_eq!(
+        temp_producer.blockchain.read().block_number(),
+        macro_block_number + 1
+    );
+}
+
+#[test]
+fn it_rejects_roll_back_beyond_pruned_revert_infos() {
+    let temp_producer = TemporaryBlockProducer::new();
+    {
+        let mut blockchain = temp_producer.blockchain.write();
+        blockchain.config.max_rebranch_depth = Some(2);
+        blockchain.config.prune_revert_infos = true;
+    }
+
+    for _ in 0..5 {
+        temp_producer.next_block(vec![], false);
+    }
+    let head_number = temp_producer.blockchain.read().block_number();
+    let head_hash = temp_producer.blockchain.read().head_hash();
+
+    // The revert infos of the blocks deeper than the maximum rebranch depth are gone.
+    assert_eq!(
+        temp_producer
+            .blockchain
+            .write()
+            .rollback_to(head_number - 3),
+        Err(PushError::InvalidFork)
+    );
+    assert_eq!(temp_producer.blockchain.read().head_hash(), head_hash);
+
+    // A rollback within the maximum rebranch depth still works.
+    temp_producer
+        .blockchain
+        .write()
+        .rollback_to(head_number - 2)
+        .unwrap();
+    assert_eq!(
+        temp_producer.blockchain.read().block_number(),
+        head_number - 2
+    );
+}
+
+#[test]
+fn it_finds_common_ancestor_with_height() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+
+    // [0] - [0] - [0] - [0] - [0]
+    //          \- [0] - [0] - [0]
+    let ancestor = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(ancestor.clone()).unwrap();
+
+    let mut main_chain = vec![];
+    let mut fork_chain = vec![];
+    for _ in 0..3 {
+        main_chain.push(temp_producer1.next_block(vec![], false));
+        fork_chain.push(temp_producer2.next_block(vec![0x42], false));
+    }
+    main_chain.push(temp_producer1.next_block(vec![], false));
+    for block in &fork_chain {
+        assert_eq!(temp_producer1.push(block.clone()), Ok(PushResult::Forked));
+    }
+
+    let blockchain = temp_producer1.blockchain.read();
+    let expected = Some((ancestor.hash(), ancestor.block_number()));
+
+    // Siblings sharing a parent.
+    assert_eq!(
+        blockchain.find_common_ancestor(&main_chain[0].hash(), &fork_chain[0].hash()),
+        expected
+    );
+
+    // A deep fork, with chains of different lengths.
+    assert_eq!(
+        blockchain.find_common_ancestor(&fork_chain[2].hash(), &main_chain[3].hash()),
+        expected
+    );
+
+    // A block on the same chain is its own ancestor.
+    assert_eq!(
+        blockchain.find_common_ancestor(&main_chain[3].hash(), &main_chain[1].hash()),
+        Some((main_chain[1].hash(), main_chain[1].block_number()))
+    );
+
+    // Unrelated hashes don't converge.
+    assert_eq!(
+        blockchain.find_common_ancestor(&main_chain[3].hash(), &Blake2bHash::default()),
+        None
+    );
+}
+
+#[test]
+fn it_breaks_ties_between_equal_forks() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    let temp_producer2 = TemporaryBlockProducer::new();
+    temp_producer1.blockchain.write().config.tie_break_forks = true;
+    temp_producer2.blockchain.write().config.tie_break_forks = true;
+
+    // [0] - [0] - [0]
+    //          \- [0]
+    let block = temp_producer1.next_block(vec![], false);
+    temp_producer2.push(block).unwrap();
+
+    let block1 = temp_producer1.next_block(vec![], false);
+    let block2 = temp_producer2.next_block(vec![0x42], false);
+    let winner = if block1.hash() < block2.hash() {
+        &block1
+    } else {
+        &block2
+    };
+
+    // Both nodes pick the fork with the smaller hash, whichever they saw first.
+    let result1 = temp_producer1.push(block2.clone());
+    let result2 = temp_producer2.push(block1.clone());
+    let expected = |own: &Block| {
+        if own == winner {
+            Ok(PushResult::Ignored)
+        } else {
+            Ok(PushResult::Rebranched)
+        }
+    };
+    assert_eq!(result1, expected(&block1));
+    assert_eq!(result2, expected(&block2));
+    assert_eq!(temp_producer1.blockchain.read().head_hash(), winner.hash());
+    assert_eq!(temp_producer2.blockchain.read().head_hash(), winner.hash());
+
+    // Shorter forks are still decided by their height.
+    let fork = temp_producer2.next_block_no_push(vec![0x43], false);
+    temp_producer1.next_block(vec![], false);
+    temp_producer1.next_block(vec![], false);
+    assert_eq!(temp_producer1.push(fork), Ok(PushResult::Forked));
+}
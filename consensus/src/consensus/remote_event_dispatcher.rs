@@ -265,6 +265,12 @@ impl<N: Network> Future for RemoteEventDispatcher<N> {
                     // If they ever become main chain blocks, they will be reported then with the respective
                     // BlockchainEvent::Rebranched(..)
                 }
+                BlockchainEvent::Ignored(..) => {
+                    // Ignored blocks are not on the main chain, they are not reported.
+                }
+                BlockchainEvent::RebranchedWithFees(_) => {
+                    // The blocks were already reported by the corresponding `Rebranched` event.
+                }
             }
             // This hash map is used to collect all the notifications for a given peer.
             let mut peer_receipts: HashMap<N::PeerId, Vec<(Blake2bHash, u32)>> = HashMap::new();
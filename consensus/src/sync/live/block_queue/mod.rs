@@ -588,11 +588,11 @@ impl<N: Network> BlockQueue<N> {
                 // Non light clients should republish all blocks to the BlockHeaderTopic.
                 if self.config.include_micro_bodies {
                     // `new_blocks` does not include the bodies. The last block adopted (the new one) needs to be fetched
-                    // with its body included.
-                    let (block_hash, _block) =
-                        new_blocks.last().expect("Rebranched with no new blocks");
-                    if let Ok(block) = self.blockchain.read().get_block(block_hash, true) {
-                        self.publish_block_header(block);
+                    // with its body included. A rollback doesn't adopt any blocks, so there is nothing to publish then.
+                    if let Some((block_hash, _block)) = new_blocks.last() {
+                        if let Ok(block) = self.blockchain.read().get_block(block_hash, true) {
+                            self.publish_block_header(block);
+                        }
                     }
                 }
 
@@ -613,6 +613,12 @@ impl<N: Network> BlockQueue<N> {
                 }
                 block_infos.push(block);
             }
+            BlockchainEvent::Ignored(..) => {
+                // The ignored block was already reported by the corresponding `Stored` event.
+            }
+            BlockchainEvent::RebranchedWithFees(_) => {
+                // The blocks were already reported by the corresponding `Rebranched` event.
+            }
         }
         block_infos
     }
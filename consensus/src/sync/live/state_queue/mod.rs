@@ -513,9 +513,12 @@ impl<N: Network> Stream for StateQueue<N> {
                 BlockchainEvent::HistoryAdopted(_) => {
                     // Nothing to do for adopted history
                 }
-                BlockchainEvent::Stored(_block) => {
+                BlockchainEvent::Stored(_block) | BlockchainEvent::Ignored(..) => {
                     // Block has not been applied so nothing to do here.
                 }
+                BlockchainEvent::RebranchedWithFees(_) => {
+                    // The rebranch was already handled by the corresponding `Rebranched` event.
+                }
             }
         }
 
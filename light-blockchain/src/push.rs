@@ -147,10 +147,19 @@ impl LightBlockchain {
         // Downgrade asap
         let this = RwLockWriteGuard::downgrade_to_upgradable(this);
 
+        let block_hash = chain_info.head.hash();
+        let block_number = chain_info.head.block_number();
         this.notifier
             .send(BlockchainEvent::Stored(chain_info.head))
             .ok();
 
+        // Inferior chain blocks additionally fire an Ignored event.
+        if result == PushResult::Ignored {
+            this.notifier
+                .send(BlockchainEvent::Ignored(block_hash, block_number))
+                .ok();
+        }
+
         Ok(result)
     }
 
@@ -140,6 +140,12 @@ impl Accounts {
         DataStore::new(&self.tree, address)
     }
 
+    /// Returns a data store over the whole accounts trie, in which accounts are keyed by their
+    /// address.
+    pub fn root_data_store(&self) -> DataStore {
+        DataStore::root(&self.tree)
+    }
+
     fn get_with_type(
         &self,
         txn: &DBTransaction,
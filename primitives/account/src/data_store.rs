@@ -22,6 +22,14 @@ impl<'tree> DataStore<'tree> {
         }
     }
 
+    /// Creates a data store over the whole trie, i.e. one whose keys are not prefixed.
+    pub fn root(tree: &'tree AccountsTrie) -> Self {
+        DataStore {
+            tree,
+            prefix: KeyNibbles::ROOT,
+        }
+    }
+
     pub fn get<T: Deserialize>(&self, txn: &TransactionProxy, key: &KeyNibbles) -> Option<T> {
         self.tree
             .get(txn, &(&self.prefix + key))
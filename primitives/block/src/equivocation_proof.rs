@@ -236,13 +236,11 @@ impl ForkProof {
         self.header2.hash()
     }
 
-    /// Verify the validity of a fork proof.
+    /// Verify the structure of a fork proof, i.e. that it contains two distinct headers in the
+    /// right order for the same slot.
     ///
-    /// Does not verify the validator address.
-    pub fn verify_excluding_address_and_network(
-        &self,
-        signing_key: &SchnorrPublicKey,
-    ) -> Result<(), EquivocationProofError> {
+    /// Does not verify the justifications nor the validator address.
+    pub fn verify_structure(&self) -> Result<(), EquivocationProofError> {
         let hash1: Blake2bHash = self.header1.hash();
         let hash2: Blake2bHash = self.header2.hash();
 
@@ -265,6 +263,21 @@ impl ForkProof {
             return Err(EquivocationProofError::SlotMismatch);
         }
 
+        Ok(())
+    }
+
+    /// Verify the validity of a fork proof.
+    ///
+    /// Does not verify the validator address.
+    pub fn verify_excluding_address_and_network(
+        &self,
+        signing_key: &SchnorrPublicKey,
+    ) -> Result<(), EquivocationProofError> {
+        self.verify_structure()?;
+
+        let hash1: Blake2bHash = self.header1.hash();
+        let hash2: Blake2bHash = self.header2.hash();
+
         // Check that the justifications are valid.
         if !signing_key.verify(&self.justification1, hash1.as_slice())
             || !signing_key.verify(&self.justification2, hash2.as_slice())
@@ -75,6 +75,8 @@ pub enum BlockError {
     ExpiredTransaction,
     #[error("Transactions execution result mismatch")]
     TransactionExecutionMismatch,
+    #[error("Transaction rejected by filter")]
+    FilteredTransaction,
 
     #[error("Justification is invalid")]
     InvalidJustification,
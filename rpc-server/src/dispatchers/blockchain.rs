@@ -587,10 +587,19 @@ impl BlockchainInterface for BlockchainDispatcher {
                     BlockchainEvent::Extended(hash) => Some(hash.into()),
                     BlockchainEvent::HistoryAdopted(hash) => Some(hash.into()),
                     BlockchainEvent::Finalized(_) | BlockchainEvent::EpochFinalized(_) => None,
-                    BlockchainEvent::Rebranched(_, new_branch) => {
-                        Some(new_branch.into_iter().last().unwrap().0.into())
+                    BlockchainEvent::Rebranched(old_branch, new_branch) => {
+                        match new_branch.into_iter().last() {
+                            Some((hash, _)) => Some(hash.into()),
+                            // A rollback doesn't adopt any blocks, the new head is the parent of
+                            // the first reverted block.
+                            None => old_branch
+                                .first()
+                                .map(|(_, block)| block.parent_hash().clone().into()),
+                        }
                     }
-                    BlockchainEvent::Stored(_block) => None,
+                    BlockchainEvent::RebranchedWithFees(_)
+                    | BlockchainEvent::Stored(_block)
+                    | BlockchainEvent::Ignored(..) => None,
                 };
                 future::ready(result)
             })
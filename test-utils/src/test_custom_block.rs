@@ -14,6 +14,7 @@ use nimiq_keys::KeyPair as SchnorrKeyPair;
 use nimiq_primitives::{
     networks::NetworkId, policy::Policy, TendermintIdentifier, TendermintStep, TendermintVote,
 };
+use nimiq_serde::Serialize;
 use nimiq_tendermint::ProposalMessage;
 use nimiq_transaction::{
     historic_transaction::HistoricTransaction, inherent::Inherent, Transaction,
@@ -40,7 +41,17 @@ pub struct BlockConfig {
     // Micro only
     pub test_micro: bool,
     pub equivocation_proofs: Vec<EquivocationProof>,
+    /// Computes the state of the block without the punishment inherents of its equivocation proofs.
+    pub omit_punishment_inherents: bool,
     pub transactions: Vec<Transaction>,
+    /// Maximum number of transactions in the block. The transactions with the highest fees are kept.
+    pub max_transactions: Option<usize>,
+    /// Maximum total serialized size of the transactions in the block. The transactions with the
+    /// highest fees are kept.
+    pub max_body_size: Option<usize>,
+    /// Prefers the transactions with the highest fee per byte instead of the highest fee when the
+    /// transactions are limited.
+    pub order_by_fee: bool,
     pub extra_data: Vec<u8>,
 
     // Macro only
@@ -70,7 +81,11 @@ impl Default for BlockConfig {
             skip_block_proof: None,
             test_micro: true,
             equivocation_proofs: vec![],
+            omit_punishment_inherents: false,
             transactions: vec![],
+            max_transactions: None,
+            max_body_size: None,
+            order_by_fee: false,
             extra_data: vec![],
             test_macro: true,
             parent_election_hash: None,
@@ -81,6 +96,62 @@ impl Default for BlockConfig {
     }
 }
 
+/// Applies a signed offset to a block number. Panics with a descriptive message if the result
+/// doesn't fit into a block number instead of silently wrapping around.
+pub fn apply_offset(base: u32, offset: i32) -> u32 {
+    base.checked_add_signed(offset).unwrap_or_else(|| {
+        panic!("Block number offset {offset} out of range for block number {base}")
+    })
+}
+
+/// Applies a signed offset to a timestamp. Panics with a descriptive message if the result
+/// doesn't fit into a timestamp instead of silently wrapping around.
+pub fn apply_timestamp_offset(base: u64, offset: i64) -> u64 {
+    base.checked_add_signed(offset)
+        .unwrap_or_else(|| panic!("Timestamp offset {offset} out of range for timestamp {base}"))
+}
+
+/// Selects the transactions of a micro block in canonical order. If the number or the total
+/// serialized size of the transactions is limited, the transactions with the highest fees (or
+/// fees per byte, if `order_by_fee` is set) are kept.
+fn select_transactions(config: &BlockConfig) -> Vec<Transaction> {
+    let mut transactions = config.transactions.clone();
+
+    if config.max_transactions.is_some() || config.max_body_size.is_some() {
+        // The sort is stable, so transactions with equal fees stay in canonical order.
+        transactions.sort_unstable();
+        if config.order_by_fee {
+            transactions.sort_by(|a, b| b.fee_per_byte().total_cmp(&a.fee_per_byte()));
+        } else {
+            transactions.sort_by(|a, b| b.fee.cmp(&a.fee));
+        }
+
+        let max_transactions = config.max_transactions.unwrap_or(usize::MAX);
+        let max_body_size = config.max_body_size.unwrap_or(usize::MAX);
+
+        let mut body_size = 0;
+        let mut selected = vec![];
+        for tx in transactions {
+            if selected.len() >= max_transactions {
+                break;
+            }
+
+            // Skip transactions that don't fit anymore, smaller ones might still do.
+            let size = tx.serialized_size();
+            if body_size + size > max_body_size {
+                continue;
+            }
+
+            body_size += size;
+            selected.push(tx);
+        }
+        transactions = selected;
+    }
+
+    transactions.sort_unstable();
+    transactions
+}
+
 /// `config` can be used to generate blocks that can be invalid in some way. config == Default creates a valid block.
 pub fn next_micro_block(
     signing_key: &SchnorrKeyPair,
@@ -91,9 +162,10 @@ pub fn next_micro_block(
         .network
         .unwrap_or_else(|| blockchain.head().network());
 
-    let block_number = (blockchain.block_number() as i32 + 1 + config.block_number_offset) as u32;
+    let block_number = apply_offset(blockchain.block_number() + 1, config.block_number_offset);
 
-    let timestamp = (blockchain.head().timestamp() as i64 + 1 + config.timestamp_offset) as u64;
+    let timestamp =
+        apply_timestamp_offset(blockchain.head().timestamp() + 1, config.timestamp_offset);
 
     let parent_hash = config
         .parent_hash
@@ -106,15 +178,18 @@ pub fn next_micro_block(
         .clone()
         .unwrap_or_else(|| prev_seed.sign_next(signing_key));
 
-    let mut transactions = config.transactions.clone();
-    transactions.sort_unstable();
+    let transactions = select_transactions(config);
 
-    let inherents = blockchain.create_punishment_inherents(
-        block_number,
-        &config.equivocation_proofs,
-        None,
-        None,
-    );
+    let inherents = if config.omit_punishment_inherents {
+        vec![]
+    } else {
+        blockchain.create_punishment_inherents(
+            block_number,
+            &config.equivocation_proofs,
+            None,
+            None,
+        )
+    };
 
     let block_state = BlockState::new(block_number, timestamp);
 
@@ -192,10 +267,10 @@ pub fn next_skip_block(
         .network
         .unwrap_or_else(|| blockchain.head().network());
 
-    let block_number = (blockchain.block_number() as i32 + 1 + config.block_number_offset) as u32;
+    let block_number = apply_offset(blockchain.block_number() + 1, config.block_number_offset);
 
     let timestamp = if config.timestamp_offset != 0 {
-        (blockchain.head().timestamp() as i64 + config.timestamp_offset) as u64
+        apply_timestamp_offset(blockchain.head().timestamp(), config.timestamp_offset)
     } else {
         blockchain.head().timestamp() + Policy::BLOCK_PRODUCER_TIMEOUT
     };
@@ -289,9 +364,9 @@ pub fn next_macro_block_proposal(
         .network
         .unwrap_or_else(|| blockchain.head().network());
 
-    let block_number = (blockchain.block_number() as i32 + 1 + config.block_number_offset) as u32;
+    let block_number = apply_offset(blockchain.block_number() + 1, config.block_number_offset);
 
-    let timestamp = (blockchain.head().timestamp() as i64 + config.timestamp_offset) as u64;
+    let timestamp = apply_timestamp_offset(blockchain.head().timestamp(), config.timestamp_offset);
 
     let parent_hash = config
         .parent_hash
@@ -343,7 +418,7 @@ pub fn next_macro_block_proposal(
     let reward_transactions = blockchain.create_reward_transactions(&header, &staking_contract);
 
     let validators = if Policy::is_election_block_at(blockchain.block_number() + 1) {
-        Some(blockchain.next_validators(&header.seed))
+        Some(blockchain.next_validators(&header.seed).unwrap())
     } else {
         None
     };
@@ -386,11 +461,13 @@ pub fn next_macro_block_proposal(
 
     let mut txn = blockchain.write_transaction();
 
-    macro_block.header.history_root = blockchain
-        .history_store
-        .add_to_history(&mut txn, block_number, &hist_txs)
-        .expect("Failed to compute history root during block production.")
-        .0;
+    macro_block.header.history_root = config.history_root.clone().unwrap_or_else(|| {
+        blockchain
+            .history_store
+            .add_to_history(&mut txn, block_number, &hist_txs)
+            .expect("Failed to compute history root during block production.")
+            .0
+    });
 
     txn.abort();
 
@@ -485,7 +562,7 @@ fn create_skip_block_proof(
         .unwrap_or_else(|| blockchain.head().seed().clone());
 
     let skip_block_info = SkipBlockInfo {
-        block_number: (blockchain.block_number() as i32 + 1 + config.block_number_offset) as u32,
+        block_number: apply_offset(blockchain.block_number() + 1, config.block_number_offset),
         vrf_entropy: seed.entropy(),
     };
 
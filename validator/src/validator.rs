@@ -480,7 +480,10 @@ where
             BlockchainEvent::Rebranched(ref old_chain, ref new_chain) => {
                 self.on_blockchain_rebranched(old_chain, new_chain)
             }
-            BlockchainEvent::Stored(ref _block) => {
+            BlockchainEvent::RebranchedWithFees(_) => {
+                // The rebranch was already handled by the corresponding `Rebranched` event.
+            }
+            BlockchainEvent::Stored(ref _block) | BlockchainEvent::Ignored(..) => {
                 // Nothing to do here for now. Forks are already reported on `fork_event_rx`
                 // and inferior chain blocks are irrelevant here.
             }
@@ -538,7 +541,16 @@ where
             self.mempool.update(new_chain, old_chain);
         }
 
-        let head_hash = &new_chain.last().expect("new_chain must not be empty").0;
+        // A rollback doesn't adopt any blocks, the new head is the parent of the first reverted
+        // block then.
+        let head_hash = match new_chain.last() {
+            Some((hash, _)) => hash,
+            None => old_chain
+                .first()
+                .expect("Rebranched without any blocks")
+                .1
+                .parent_hash(),
+        };
         self.init_block_producer(Some(head_hash));
     }
 
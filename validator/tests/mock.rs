@@ -91,6 +91,91 @@ async fn one_validator_can_create_micro_blocks() {
     assert!(consensus1.blockchain.read().block_number() >= 10 + Policy::genesis_block_number());
 }
 
+#[test(tokio::test)]
+async fn one_validator_keeps_producing_after_rollback() {
+    let hub = MockHub::default();
+    let env = VolatileDatabase::new(20).expect("Could not open a volatile database");
+
+    let voting_key = BlsKeyPair::generate(&mut seeded_rng(0));
+    let validator_key = KeyPair::generate(&mut seeded_rng(0));
+    let fee_key = KeyPair::generate(&mut seeded_rng(0));
+    let signing_key = KeyPair::generate(&mut seeded_rng(0));
+    let genesis = GenesisBuilder::default()
+        .with_network(NetworkId::UnitAlbatross)
+        .with_genesis_block_number(Policy::genesis_block_number())
+        .with_genesis_validator(
+            Address::from(&validator_key),
+            signing_key.public,
+            voting_key.public_key,
+            Address::default(),
+            None,
+            None,
+            false,
+        )
+        .generate(env)
+        .unwrap();
+
+    let (validator, mut consensus1) = build_validator::<Network>(
+        0,
+        Address::from(&validator_key),
+        false,
+        signing_key,
+        voting_key,
+        fee_key,
+        genesis.clone(),
+        &mut Some(hub),
+        false,
+    )
+    .await;
+
+    consensus1.force_established();
+    assert!(consensus1.is_established());
+
+    let blockchain = Arc::clone(&validator.blockchain);
+    spawn(validator);
+
+    let events = blockchain.read().notifier_as_stream();
+    events.take(5).for_each(|_| future::ready(())).await;
+
+    // Roll back the last two blocks, which announces a rebranch without adopted blocks.
+    let mut events = blockchain.read().notifier_as_stream();
+    let old_head_number = {
+        let mut blockchain = blockchain.write();
+        let head_number = blockchain.block_number();
+        blockchain.rollback_to(head_number - 2).unwrap();
+        head_number
+    };
+    loop {
+        match events.next().await {
+            Some(BlockchainEvent::Rebranched(old_chain, new_chain)) => {
+                assert_eq!(old_chain.len(), 2);
+                assert!(new_chain.is_empty());
+                break;
+            }
+            Some(_) => {}
+            None => panic!("Event stream ended before the rollback was announced"),
+        }
+    }
+
+    // The validator handles the rollback and continues producing blocks on top of the new head.
+    let blockchain2 = Arc::clone(&blockchain);
+    let stop_fut = future::poll_fn(move |_cx| {
+        if blockchain2.read().block_number() <= old_head_number {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    });
+    timeout(
+        Duration::from_secs(30),
+        events.take_until(stop_fut).for_each(|_| future::ready(())),
+    )
+    .await
+    .unwrap();
+
+    assert!(blockchain.read().block_number() > old_head_number);
+}
+
 #[test(tokio::test)]
 async fn four_validators_can_create_micro_blocks() {
     let hub = MockHub::default();
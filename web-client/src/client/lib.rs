@@ -982,7 +982,15 @@ impl Client {
                             (hash, "finalized", Array::new(), adopted_blocks)
                         }
                         Some(BlockchainEvent::Rebranched(old_chain, new_chain)) => {
-                            let hash = &new_chain.last().unwrap().0.clone();
+                            // A rollback doesn't adopt any blocks, the new head is the parent of
+                            // the first reverted block.
+                            let hash = match new_chain.last() {
+                                Some((hash, _)) => hash.clone(),
+                                None => match old_chain.first() {
+                                    Some((_, block)) => block.parent_hash().clone(),
+                                    None => continue,
+                                },
+                            };
 
                             let reverted_blocks = Array::new();
                             for (h, _) in old_chain {
@@ -994,16 +1002,19 @@ impl Client {
                                 adopted_blocks.push(&h.to_hex().into());
                             }
 
-                            (
-                                hash.to_owned(),
-                                "rebranched",
-                                reverted_blocks,
-                                adopted_blocks,
-                            )
+                            (hash, "rebranched", reverted_blocks, adopted_blocks)
                         }
                         Some(BlockchainEvent::Stored(block)) => {
                             (block.hash(), "stored", Array::new(), Array::new())
                         }
+                        Some(BlockchainEvent::Ignored(..)) => {
+                            // Ignored blocks are already reported as stored.
+                            continue;
+                        }
+                        Some(BlockchainEvent::RebranchedWithFees(_)) => {
+                            // Rebranches are already reported as rebranched.
+                            continue;
+                        }
                         None => {
                             break;
                         }
@@ -0,0 +1,63 @@
+//! This module contains helpers to count the R1CS constraints of the circuits. This makes changes
+//! in the proving cost of the circuits visible.
+
+use ark_ff::Field;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, OptimizationGoal};
+use rand::thread_rng;
+
+use crate::circuits::{mnt4, mnt6};
+
+/// Synthesizes the given circuit and returns its number of constraints.
+pub fn count_constraints<C: ConstraintSynthesizer<F>, F: Field>(circuit: C) -> usize {
+    let cs = ConstraintSystem::new_ref();
+    cs.set_optimization_goal(OptimizationGoal::Constraints);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    cs.num_constraints()
+}
+
+/// Returns the number of constraints of the Macro Block circuit.
+pub fn macro_block_constraints() -> usize {
+    count_constraints(mnt6::MacroBlockCircuit::rand(&mut thread_rng()))
+}
+
+/// Returns the number of constraints of the Macro Block Wrapper circuit.
+pub fn macro_block_wrapper_constraints() -> usize {
+    count_constraints(mnt4::MacroBlockWrapperCircuit::rand(&mut thread_rng()))
+}
+
+/// Returns the number of constraints of the Merger circuit.
+pub fn merger_constraints() -> usize {
+    count_constraints(mnt6::MergerCircuit::rand(&mut thread_rng()))
+}
+
+/// Returns the number of constraints of the Merger Wrapper circuit.
+pub fn merger_wrapper_constraints() -> usize {
+    count_constraints(mnt4::MergerWrapperCircuit::rand(&mut thread_rng()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The wrapper circuits are proven over MNT6-753, whose largest evaluation domain is below 2^20.
+    const MAX_WRAPPER_CONSTRAINTS: usize = 1 << 20;
+    const MAX_MERGER_CONSTRAINTS: usize = 1 << 22;
+    const MAX_MACRO_BLOCK_CONSTRAINTS: usize = 1 << 24;
+
+    #[test]
+    #[cfg_attr(not(feature = "expensive-tests"), ignore)]
+    fn constraint_counts_are_within_bounds() {
+        let macro_block = macro_block_constraints();
+        assert!(macro_block > 0 && macro_block < MAX_MACRO_BLOCK_CONSTRAINTS);
+
+        let macro_block_wrapper = macro_block_wrapper_constraints();
+        assert!(macro_block_wrapper > 0 && macro_block_wrapper < MAX_WRAPPER_CONSTRAINTS);
+
+        let merger = merger_constraints();
+        assert!(merger > 0 && merger < MAX_MERGER_CONSTRAINTS);
+
+        let merger_wrapper = merger_wrapper_constraints();
+        assert!(merger_wrapper > 0 && merger_wrapper < MAX_WRAPPER_CONSTRAINTS);
+    }
+}
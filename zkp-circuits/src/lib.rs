@@ -3,6 +3,8 @@ mod blake2s;
 #[cfg(feature = "zkp-prover")]
 pub mod circuits;
 #[cfg(feature = "zkp-prover")]
+pub mod constraints;
+#[cfg(feature = "zkp-prover")]
 pub(crate) mod gadgets;
 pub mod metadata;
 #[cfg(feature = "zkp-prover")]
@@ -2,8 +2,10 @@ use std::{path::Path, sync::Arc};
 
 use ark_groth16::Proof;
 use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_database::volatile::VolatileDatabase;
+use nimiq_genesis::NetworkInfo;
 use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_test_log::test;
 use nimiq_test_utils::{
@@ -12,7 +14,7 @@ use nimiq_test_utils::{
     zkp_test_data::{get_base_seed, simulate_merger_wrapper, ZKP_TEST_KEYS_PATH},
 };
 use nimiq_utils::time::OffsetTime;
-use nimiq_zkp::ZKP_VERIFYING_DATA;
+use nimiq_zkp::{verify::verify_merger_wrappers_batch, ZKP_VERIFYING_DATA};
 use nimiq_zkp_component::{
     proof_store::{DBProofStore, ProofStore},
     proof_utils::validate_proof,
@@ -149,6 +151,56 @@ async fn can_detect_valid_proof_none_genesis_blocks() {
     );
 }
 
+#[test(tokio::test)]
+async fn can_batch_verify_proofs() {
+    let blockchain = blockchain();
+    let genesis_hash = NetworkInfo::from_network_id(blockchain.read().network_id())
+        .genesis_block()
+        .unwrap_macro()
+        .hash_blake2s();
+
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    let mut proofs = vec![];
+    for _ in 0..2 {
+        produce_macro_blocks_with_rng(
+            &producer,
+            &blockchain,
+            Policy::batches_per_epoch() as usize,
+            &mut get_base_seed(),
+        );
+
+        let zkp_proof = simulate_merger_wrapper(
+            Path::new(ZKP_TEST_KEYS_PATH),
+            &blockchain,
+            &ZKP_VERIFYING_DATA,
+            &mut get_base_seed(),
+        );
+        let final_hash = blockchain.read().state.election_head.hash_blake2s();
+        proofs.push((genesis_hash.clone(), final_hash, zkp_proof.proof.unwrap()));
+    }
+
+    assert!(
+        verify_merger_wrappers_batch(&proofs, &ZKP_VERIFYING_DATA).unwrap(),
+        "The batch verification of valid proofs failed"
+    );
+
+    // Replace one of the proofs by an invalid one.
+    let mut invalid_proofs = proofs.clone();
+    invalid_proofs[1].2 = Proof::default();
+    assert!(
+        !verify_merger_wrappers_batch(&invalid_proofs, &ZKP_VERIFYING_DATA).unwrap(),
+        "The batch verification with an invalid proof should fail"
+    );
+
+    // Use a valid proof for the wrong final block.
+    let mut invalid_proofs = proofs;
+    invalid_proofs[0].1 = invalid_proofs[1].1.clone();
+    assert!(
+        !verify_merger_wrappers_batch(&invalid_proofs, &ZKP_VERIFYING_DATA).unwrap(),
+        "The batch verification with a proof for the wrong block should fail"
+    );
+}
+
 #[test(tokio::test)]
 async fn can_store_and_load_zkp_state_from_db() {
     let env = VolatileDatabase::new(1).unwrap();
@@ -4,6 +4,7 @@ use ark_mnt6_753::MNT6_753;
 #[cfg(feature = "zkp-prover")]
 pub mod prove;
 pub mod verify;
+pub mod vk_cache;
 
 /// This is the proof type for the NanoZKP. It is just an alias, for convenience.
 pub type NanoProof = Proof<MNT6_753>;
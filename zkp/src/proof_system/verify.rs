@@ -1,7 +1,8 @@
 use ark_crypto_primitives::snark::SNARK;
-use ark_ff::ToConstraintField;
-use ark_groth16::{Groth16, Proof};
-use ark_mnt6_753::MNT6_753;
+use ark_ec::{pairing::Pairing, CurveGroup};
+use ark_ff::{One, ToConstraintField, UniformRand, Zero};
+use ark_groth16::{prepare_verifying_key, Groth16, Proof};
+use ark_mnt6_753::{Fr, MNT6_753};
 use nimiq_hash::Blake2sHash;
 use nimiq_zkp_primitives::{NanoZKPError, VerifyingData};
 
@@ -30,3 +31,68 @@ pub fn verify(
     // Return result.
     Ok(result)
 }
+
+/// This function verifies several proofs for the Merger Wrapper circuit at once. Each entry
+/// consists of the header hash of the initial block, the header hash of the final block and the
+/// SNARK proof. It returns false if any of the proofs is invalid.
+///
+/// The Groth16 verification equations of all proofs are combined using random coefficients, so
+/// that only a single multi-pairing needs to be computed instead of one pairing check per proof.
+pub fn verify_merger_wrappers_batch(
+    proofs: &[(Blake2sHash, Blake2sHash, Proof<MNT6_753>)],
+    verifying_data: &VerifyingData,
+) -> Result<bool, NanoZKPError> {
+    if proofs.is_empty() {
+        return Ok(true);
+    }
+
+    let vk = &verifying_data.merger_wrapper_vk;
+    let pvk = prepare_verifying_key(vk);
+    let mut rng = rand::thread_rng();
+
+    // The keys commitment is the same for all proofs.
+    let keys_commitment: Vec<Fr> = verifying_data.keys_commitment.to_field_elements().unwrap();
+
+    let mut g1_terms = Vec::with_capacity(proofs.len() + 3);
+    let mut g2_terms = Vec::with_capacity(proofs.len() + 3);
+
+    let mut sum_coefficients = Fr::zero();
+    let mut sum_inputs = <MNT6_753 as Pairing>::G1::zero();
+    let mut sum_c = <MNT6_753 as Pairing>::G1::zero();
+
+    for (i, (genesis_header_hash, final_header_hash, proof)) in proofs.iter().enumerate() {
+        // Prepare the inputs.
+        let mut inputs = vec![];
+
+        inputs.append(&mut genesis_header_hash.0.to_field_elements().unwrap());
+        inputs.append(&mut final_header_hash.0.to_field_elements().unwrap());
+        inputs.extend_from_slice(&keys_commitment);
+
+        let prepared_inputs = Groth16::<MNT6_753>::prepare_inputs(&pvk, &inputs)?;
+
+        // The first coefficient can be one without affecting soundness.
+        let coefficient = if i == 0 {
+            Fr::one()
+        } else {
+            Fr::rand(&mut rng)
+        };
+
+        g1_terms.push((proof.a * coefficient).into_affine());
+        g2_terms.push(proof.b);
+
+        sum_coefficients += coefficient;
+        sum_inputs += prepared_inputs * coefficient;
+        sum_c += proof.c * coefficient;
+    }
+
+    // Check that prod e(r_i * A_i, B_i) * e(-sum r_i * L_i, gamma) * e(-sum r_i * C_i, delta)
+    // * e(-(sum r_i) * alpha, beta) is the identity.
+    g1_terms.push((-sum_inputs).into_affine());
+    g2_terms.push(vk.gamma_g2);
+    g1_terms.push((-sum_c).into_affine());
+    g2_terms.push(vk.delta_g2);
+    g1_terms.push((vk.alpha_g1 * (-sum_coefficients)).into_affine());
+    g2_terms.push(vk.beta_g2);
+
+    Ok(MNT6_753::multi_pairing(g1_terms, g2_terms).is_zero())
+}
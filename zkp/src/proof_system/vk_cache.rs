@@ -0,0 +1,64 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ark_ec::pairing::Pairing;
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalDeserialize;
+use nimiq_zkp_primitives::NanoZKPError;
+use parking_lot::RwLock;
+
+/// A cache of verifying keys that were loaded from files, keyed by the path of the file. Parsing
+/// a verifying key is expensive, so the cache allows repeated proofs to reuse the keys. It can be
+/// shared between the threads of a proving thread pool.
+pub struct VerifyingKeyCache<E: Pairing> {
+    keys: RwLock<HashMap<PathBuf, Arc<VerifyingKey<E>>>>,
+}
+
+impl<E: Pairing> Default for VerifyingKeyCache<E> {
+    fn default() -> Self {
+        Self {
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+}
+
+impl<E: Pairing> VerifyingKeyCache<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the verifying key stored in the given file. The file is only read and parsed if
+    /// the key isn't cached yet.
+    pub fn get_or_load(&self, path: &Path) -> Result<Arc<VerifyingKey<E>>, NanoZKPError> {
+        if let Some(key) = self.keys.read().get(path) {
+            return Ok(Arc::clone(key));
+        }
+
+        // Load the verifying key from file without holding the lock.
+        let mut file = File::open(path)?;
+        let key = Arc::new(VerifyingKey::deserialize_uncompressed_unchecked(&mut file)?);
+
+        // Another thread might have loaded the same key in the meantime, keep the first one.
+        let mut keys = self.keys.write();
+        Ok(Arc::clone(keys.entry(path.to_path_buf()).or_insert(key)))
+    }
+
+    /// Returns the number of cached verifying keys.
+    pub fn len(&self) -> usize {
+        self.keys.read().len()
+    }
+
+    /// Returns true if no verifying keys are cached.
+    pub fn is_empty(&self) -> bool {
+        self.keys.read().is_empty()
+    }
+
+    /// Removes all cached verifying keys.
+    pub fn clear(&self) {
+        self.keys.write().clear();
+    }
+}
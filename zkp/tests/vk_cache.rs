@@ -0,0 +1,46 @@
+use std::{env, fs, fs::File, sync::Arc};
+
+use ark_ec::CurveGroup;
+use ark_groth16::VerifyingKey;
+use ark_mnt6_753::{G1Projective, G2Projective, MNT6_753};
+use ark_serialize::CanonicalSerialize;
+use ark_std::UniformRand;
+use nimiq_test_log::test;
+use nimiq_test_utils::test_rng::test_rng;
+use nimiq_zkp::vk_cache::VerifyingKeyCache;
+
+#[test]
+fn can_cache_verifying_keys() {
+    let rng = &mut test_rng(false);
+
+    // Write a random verifying key to a file.
+    let vk = VerifyingKey::<MNT6_753> {
+        alpha_g1: G1Projective::rand(rng).into_affine(),
+        beta_g2: G2Projective::rand(rng).into_affine(),
+        gamma_g2: G2Projective::rand(rng).into_affine(),
+        delta_g2: G2Projective::rand(rng).into_affine(),
+        gamma_abc_g1: (0..4)
+            .map(|_| G1Projective::rand(rng).into_affine())
+            .collect(),
+    };
+    let path = env::temp_dir().join(format!("nimiq_vk_cache_{}.bin", std::process::id()));
+    let mut file = File::create(&path).unwrap();
+    vk.serialize_uncompressed(&mut file).unwrap();
+
+    let cache = VerifyingKeyCache::<MNT6_753>::new();
+    assert!(cache.is_empty());
+
+    let first = cache.get_or_load(&path).unwrap();
+    assert_eq!(*first, vk);
+
+    // The second lookup hits the cache, so it doesn't need the file anymore.
+    fs::remove_file(&path).unwrap();
+    let second = cache.get_or_load(&path).unwrap();
+    assert_eq!(*second, vk);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(cache.len(), 1);
+
+    // Once cleared, the key has to be loaded from the file again.
+    cache.clear();
+    assert!(cache.get_or_load(&path).is_err());
+}